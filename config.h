@@ -2,7 +2,18 @@
 
 /* appearance */
 static const unsigned int borderpx = 1; /* border pixel of windows */
+const int smartborders = 1; /* 1 means no border when a window is alone */
 static const unsigned int snap = 32;    /* snap pixel */
+/* where new floating windows go, PlaceCenter or PlaceParent center them */
+static const int floatplacement = PlaceRequested;
+const unsigned int gappih = 10; /* horiz inner gap between windows */
+const unsigned int gappiv = 10; /* vert inner gap between windows */
+const unsigned int gappoh = 10; /* horiz outer gap between windows and edge */
+const unsigned int gappov = 10; /* vert outer gap between windows and edge */
+const int smartgaps = 0; /* 1 means no gaps when there is only one window */
+const int toptab = 1;      /* 0 means the tab bar is below the windows */
+const int tabautohide = 1; /* 1 means no tab bar with a single window */
+const float bspratio = 0.5; /* share of a bsp split given to the first side */
 static const int showbar = 1;           /* 0 means no bar */
 static const int topbar = 1;            /* 0 means bottom bar */
 static const char *fonts[] = {"NotoSansMono Nerd Font:size=14",
@@ -16,7 +27,22 @@ static const unsigned int systrayspacing = 2; /* systray spacing */
 static const int systraypinningfailfirst =
     1; /* 1: if pinning fails, display systray on the first monitor, False:
           display systray on the last monitor*/
-static const int showsystray = 1; /* 0 means no systray */
+const int showsystray = 1; /* 0 means no systray */
+/* status program that gets signalled when a status block is clicked, it is
+ * found by the pid in statuspidfile or by name when that is NULL */
+const char *statusbar = "dwmblocks";
+const char *statuspidfile = NULL;
+
+/* bar widgets from left to right, each side is packed towards its edge */
+const Widget barwidgets[] = {
+    /* widget       alignment */
+    {WidgetTags, BarLeft},
+    {WidgetLtSymbol, BarLeft},
+    {WidgetTitle, BarLeft},
+    {WidgetStatus, BarRight},
+    {WidgetSystray, BarRight},
+};
+const int nbarwidgets = LENGTH(barwidgets);
 
 static const char col_gray1[] = "#222222";
 static const char col_gray2[] = "#444444";
@@ -42,34 +68,87 @@ static const unsigned int alphas[][3] = {
 /* tagging */
 const char *tags[] = {"1", "2", "3", "4", "5", "6", "7", "8", "9"};
 
+/* scratchpads, the rule with SPTAG(i) catches the window of scratchpad i */
+static const char *spterm[] = {"alacritty", "--class", "spterm", NULL};
+static const Scratchpad scratchpads[] = {
+    /* name     command */
+    {"spterm", spterm},
+};
+
 static const Rule rules[] = {
     /* xprop(1):
      *	WM_CLASS(STRING) = instance, class
      *	WM_NAME(STRING) = title
+     * every matching rule is applied in order, a later rule overrides the
+     * flags, adds its tags and overrides the fields it sets, -1 and NULL
+     * leave a field alone
      */
-    /* class      instance    title       tags mask     isfloating   monitor */
-    {"Gimp", NULL, NULL, 0, 1, -1},
-    {"Firefox", NULL, NULL, 1 << 8, 0, -1},
+    /* class      instance    title       tags mask     isfloating
+     * isterminal noswallow   monitor     border width  placement
+     * floating geometry */
+    {"Gimp", NULL, NULL, 0, 1, 0, 0, -1, -1, PlaceLeastOverlap, NULL},
+    {"Firefox", NULL, NULL, 1 << 8, 0, 0, 0, -1, -1, -1, NULL},
+    {"Alacritty", NULL, NULL, 0, 0, 1, 0, -1, -1, -1, NULL},
+    {"Pavucontrol", NULL, NULL, 0, 1, 0, 0, -1, -1, PlaceCenter, "40%x50%"},
+    {"Gnome-calculator", NULL, NULL, 0, 1, 0, 0, -1, -1, -1, "-0+0"},
+    {NULL, "spterm", NULL, SPTAG(0), 1, 0, 0, -1, -1, -1, NULL},
+    {NULL, NULL, "Event Tester", 0, 0, 0, 1, -1, -1, -1, NULL}, /* xev */
 };
 
 /* layout(s) */
 static const float mfact = 0.55; /* factor of master area size [0.05..0.95] */
 static const int nmaster = 1;    /* number of clients in master area */
+static const int mastercols = 0; /* 1 means master windows side by side */
 static const int resizehints =
     1; /* 1 means respect size hints in tiled resizals */
 
 static const Layout layouts[] = {
-    /* symbol     arrange function */
+    /* symbol     arrange function  flextile layout string */
     {"[]=", tile}, /* first entry is default */
     {"><>", NULL}, /* no layout function means floating behavior */
     {"[M]", rust_monocle},
+    {"[@]", rust_spiral},
+    {"[\\]", rust_dwindle},
+    {"|M|", rust_centered_master},
+    {">M>", rust_centered_floating_master},
+    {"TTT", rust_bstack},
+    {"===", rust_bstack_horiz},
+    {"HHH", rust_grid},
+    {"###", rust_gapless_grid},
+    {"[D]", rust_deck},
+    {"[T]", rust_tabbed},
+    {"[B]", rust_bsp},
+    {"||=", rust_column_master},
+    {"|||", rust_three_column},
+    {"TT=", rust_flextile, "tcc2"}, /* bottom stack with two stack rows */
+    {"[]#", rust_flextile, "lrg1"}, /* tile with the stack in a grid */
+};
+
+/* per tag defaults, the first entry is used when all tags are viewed and
+ * tags without an entry use the defaults above */
+static const TagDefault tagdefaults[] = {
+    /* layout  mfact  nmaster  showbar */
+    {-1, 0, -1, -1}, /* all tags */
+    {-1, 0, -1, -1}, /* 1 */
+    {-1, 0, -1, -1}, /* 2 */
+    {-1, 0, -1, -1}, /* 3 */
+};
+
+/* default layout of a monitor by output name and aspect ratio, the first
+ * matching rule is used when the monitor shows up and again when a size
+ * change makes another rule match, like rotating it into portrait */
+static const MonitorRule monrules[] = {
+    /* output  min aspect  max aspect  layout */
+    {NULL, 0, 0.9, 7}, /* portrait, bottom stack */
+    {NULL, 2.0, 0, 5}, /* ultrawide, centered master */
+    {NULL, 0, 0, 0},   /* everything else, tile */
 };
 
 /* key definitions */
 #define MODKEY Mod1Mask
 #define TAGKEYS(KEY, TAG)                                                      \
   {MODKEY, KEY, rust_view, {.ui = 1 << TAG}},                                  \
-      {MODKEY | ControlMask, KEY, toggleview, {.ui = 1 << TAG}},               \
+      {MODKEY | ControlMask, KEY, rust_toggle_view, {.ui = 1 << TAG}},         \
       {MODKEY | ShiftMask, KEY, tag, {.ui = 1 << TAG}},                        \
       {MODKEY | ControlMask | ShiftMask, KEY, toggletag, {.ui = 1 << TAG}},
 
@@ -93,6 +172,7 @@ static Key keys[] = {
     {MODKEY, XK_d, spawn, {.v = dmenucmd}},
     {MODKEY, XK_Return, spawn, {.v = termcmd}},
     {MODKEY | ShiftMask, XK_Return, spawn, {.v = editorcmd}},
+    {MODKEY, XK_grave, togglescratch, {.v = "spterm"}},
 
     {MODKEY, XK_b, togglebar, {0}},
 
@@ -102,6 +182,9 @@ static Key keys[] = {
     {MODKEY | ShiftMask, XK_d, incnmaster, {.i = -1}},
     {MODKEY, XK_h, setmfact, {.f = -0.05}},
     {MODKEY, XK_l, setmfact, {.f = +0.05}},
+    {MODKEY | ShiftMask, XK_h, rust_set_cfact, {.f = -0.25}},
+    {MODKEY | ShiftMask, XK_l, rust_set_cfact, {.f = +0.25}},
+    {MODKEY | ShiftMask, XK_o, rust_set_cfact, {.f = 0.00}},
 
     {MODKEY, XK_s, zoom, {0}},
     {MODKEY, XK_o, rust_view, {0}},
@@ -109,6 +192,7 @@ static Key keys[] = {
 
     {MODKEY, XK_space, setlayout, {0}},
     {MODKEY | ShiftMask, XK_space, togglefloating, {0}},
+    {MODKEY | ShiftMask, XK_s, togglesticky, {0}},
 
     {MODKEY, XK_0, rust_view, {.ui = ~0}},
     {MODKEY | ShiftMask, XK_0, tag, {.ui = ~0}},
@@ -118,10 +202,54 @@ static Key keys[] = {
     {MODKEY | ShiftMask, XK_comma, tagmon, {.i = -1}},
     {MODKEY | ShiftMask, XK_period, tagmon, {.i = +1}},
 
+    // Gaps
+    {MODKEY | Mod4Mask, XK_h, rust_inc_gaps, {.i = +1}},
+    {MODKEY | Mod4Mask, XK_l, rust_inc_gaps, {.i = -1}},
+    {MODKEY | Mod4Mask | ShiftMask, XK_h, rust_inc_outer_gaps, {.i = +1}},
+    {MODKEY | Mod4Mask | ShiftMask, XK_l, rust_inc_outer_gaps, {.i = -1}},
+    {MODKEY | Mod4Mask | ControlMask, XK_h, rust_inc_inner_gaps, {.i = +1}},
+    {MODKEY | Mod4Mask | ControlMask, XK_l, rust_inc_inner_gaps, {.i = -1}},
+    {MODKEY | Mod4Mask, XK_0, rust_toggle_gaps, {0}},
+    {MODKEY | Mod4Mask | ShiftMask, XK_0, rust_default_gaps, {0}},
+
     // Layout keybindings
     {MODKEY, XK_i, setlayout, {.v = &layouts[0]}},
     {MODKEY, XK_o, setlayout, {.v = &layouts[1]}},
     {MODKEY, XK_p, setlayout, {.v = &layouts[2]}},
+    {MODKEY, XK_u, setlayout, {.v = &layouts[3]}},
+    {MODKEY | ShiftMask, XK_u, setlayout, {.v = &layouts[4]}},
+    {MODKEY, XK_c, setlayout, {.v = &layouts[5]}},
+    {MODKEY | ShiftMask, XK_c, setlayout, {.v = &layouts[6]}},
+    {MODKEY, XK_n, setlayout, {.v = &layouts[7]}},
+    {MODKEY | ShiftMask, XK_n, setlayout, {.v = &layouts[8]}},
+    {MODKEY, XK_g, setlayout, {.v = &layouts[9]}},
+    {MODKEY | ShiftMask, XK_g, setlayout, {.v = &layouts[10]}},
+    {MODKEY, XK_e, setlayout, {.v = &layouts[11]}},
+    {MODKEY, XK_t, setlayout, {.v = &layouts[12]}},
+    {MODKEY, XK_w, setlayout, {.v = &layouts[13]}},
+    {MODKEY, XK_y, setlayout, {.v = &layouts[14]}},
+    {MODKEY | ShiftMask, XK_y, setlayout, {.v = &layouts[15]}},
+    {MODKEY, XK_a, setlayout, {.v = &layouts[16]}},
+    {MODKEY | ShiftMask, XK_a, setlayout, {.v = &layouts[17]}},
+    {MODKEY | ShiftMask, XK_m, rust_toggle_master_columns, {0}},
+    {MODKEY, XK_x, rust_toggle_layout_modifier, {.ui = LtMirrorH}},
+    {MODKEY | ShiftMask, XK_x, rust_toggle_layout_modifier, {.ui = LtMirrorV}},
+    {MODKEY, XK_r, rust_toggle_layout_modifier, {.ui = LtRotate}},
+
+    /* bsp layout */
+    {MODKEY | ControlMask, XK_h, rust_bsp_preselect, {.i = DirLeft}},
+    {MODKEY | ControlMask, XK_j, rust_bsp_preselect, {.i = DirDown}},
+    {MODKEY | ControlMask, XK_k, rust_bsp_preselect, {.i = DirUp}},
+    {MODKEY | ControlMask, XK_l, rust_bsp_preselect, {.i = DirRight}},
+    {MODKEY | ControlMask | ShiftMask, XK_h, rust_bsp_swap, {.i = DirLeft}},
+    {MODKEY | ControlMask | ShiftMask, XK_j, rust_bsp_swap, {.i = DirDown}},
+    {MODKEY | ControlMask | ShiftMask, XK_k, rust_bsp_swap, {.i = DirUp}},
+    {MODKEY | ControlMask | ShiftMask, XK_l, rust_bsp_swap, {.i = DirRight}},
+    {MODKEY | ControlMask, XK_r, rust_bsp_rotate, {0}},
+    {MODKEY | ControlMask, XK_f, rust_bsp_flip, {0}},
+    {MODKEY | ControlMask, XK_b, rust_bsp_balance, {0}},
+    {MODKEY | ControlMask, XK_bracketleft, rust_bsp_resize, {.f = -0.05}},
+    {MODKEY | ControlMask, XK_bracketright, rust_bsp_resize, {.f = +0.05}},
 
     // Tags
     TAGKEYS(XK_1, 0) TAGKEYS(XK_2, 1) TAGKEYS(XK_3, 2) TAGKEYS(XK_4, 3)
@@ -133,19 +261,27 @@ static Key keys[] = {
 };
 
 /* button definitions */
-/* click can be ClkTagBar, ClkLtSymbol, ClkStatusText, ClkWinTitle,
- * ClkClientWin, or ClkRootWin */
+/* click can be ClkTagBar, ClkLtSymbol, ClkStatusText, ClkStatusBlock,
+ * ClkWinTitle, ClkTabBar, ClkClientWin, or ClkRootWin. ClkStatusText is the
+ * status text outside of the blocks. */
 static Button buttons[] = {
     /* click                event mask      button          function argument */
     {ClkLtSymbol, 0, Button1, setlayout, {0}},
     {ClkLtSymbol, 0, Button3, setlayout, {.v = &layouts[2]}},
     {ClkWinTitle, 0, Button2, zoom, {0}},
+    {ClkTabBar, 0, Button1, rust_focus_tab, {0}},
     {ClkStatusText, 0, Button2, spawn, {.v = termcmd}},
+    {ClkStatusBlock, 0, Button1, rust_signal_status, {.i = 1}},
+    {ClkStatusBlock, 0, Button2, rust_signal_status, {.i = 2}},
+    {ClkStatusBlock, 0, Button3, rust_signal_status, {.i = 3}},
+    {ClkStatusBlock, 0, Button4, rust_signal_status, {.i = 4}},
+    {ClkStatusBlock, 0, Button5, rust_signal_status, {.i = 5}},
     {ClkClientWin, MODKEY, Button1, movemouse, {0}},
     {ClkClientWin, MODKEY, Button2, togglefloating, {0}},
     {ClkClientWin, MODKEY, Button3, resizemouse, {0}},
+    {ClkClientWin, MODKEY | ShiftMask, Button1, dragsplit, {0}},
     {ClkTagBar, 0, Button1, rust_view, {0}},
-    {ClkTagBar, 0, Button3, toggleview, {0}},
+    {ClkTagBar, 0, Button3, rust_toggle_view, {0}},
     {ClkTagBar, MODKEY, Button1, tag, {0}},
     {ClkTagBar, MODKEY, Button3, toggletag, {0}},
 };
@@ -40,6 +40,9 @@
 #ifdef XINERAMA
 #include <X11/extensions/Xinerama.h>
 #endif /* XINERAMA */
+#ifdef XRANDR
+#include <X11/extensions/Xrandr.h>
+#endif /* XRANDR */
 #include <X11/Xft/Xft.h>
 
 #include "drw.h"
@@ -54,12 +57,15 @@
 #define INTERSECT(x, y, w, h, m)                                               \
   (MAX(0, MIN((x) + (w), (m)->wx + (m)->ww) - MAX((x), (m)->wx)) *             \
    MAX(0, MIN((y) + (h), (m)->wy + (m)->wh) - MAX((y), (m)->wy)))
-#define ISVISIBLE(C) ((C->tags & C->mon->tagset[C->mon->seltags]))
+#define ISVISIBLE(C)                                                           \
+  ((C->tags & C->mon->tagset[C->mon->seltags]) || C->issticky)
 #define LENGTH(X) (sizeof X / sizeof X[0])
 #define MOUSEMASK (BUTTONMASK | PointerMotionMask)
 #define WIDTH(X) ((X)->w + 2 * (X)->bw)
 #define HEIGHT(X) ((X)->h + 2 * (X)->bw)
 #define TAGMASK ((1 << LENGTH(tags)) - 1)
+#define SPTAG(i) ((1 << LENGTH(tags)) << (i)) /* hidden tag of a scratchpad */
+#define SPTAGMASK (((1 << LENGTH(scratchpads)) - 1) << LENGTH(tags))
 #define TEXTW(X) (drw_fontset_getwidth(drw, (X)) + lrpad)
 
 #define SYSTEM_TRAY_REQUEST_DOCK 0
@@ -93,6 +99,7 @@ enum {
   NetSystemTrayOrientation,
   NetSystemTrayOrientationHorz,
   NetWMFullscreen,
+  NetWMSticky,
   NetActiveWindow,
   NetWMWindowType,
   NetWMWindowTypeDialog,
@@ -112,10 +119,43 @@ enum {
   ClkLtSymbol,
   ClkStatusText,
   ClkWinTitle,
+  ClkTabBar,
   ClkClientWin,
   ClkRootWin,
+  ClkStatusBlock, /* a block of the status text, see rust_signal_status */
   ClkLast
 }; /* clicks */
+enum { DirLeft, DirRight, DirUp, DirDown }; /* directions */
+enum {
+  LtMirrorH = 1 << 0, /* master area on the right */
+  LtMirrorV = 1 << 1, /* top and bottom swapped */
+  LtRotate = 1 << 2   /* turned by 90 degrees */
+}; /* layout modifiers */
+enum {
+  SplitNone,
+  SplitLeft,    /* master area on the left, mfact is its share of the width */
+  SplitRight,   /* master area on the right */
+  SplitTop,     /* master area on top, mfact is its share of the height */
+  SplitBottom,  /* master area at the bottom */
+  SplitCenterH, /* master area between two stack columns */
+  SplitCenterV  /* master area between two stack rows */
+}; /* master split */
+enum { StackNone, StackRows, StackColumns }; /* how cfact shares the stack */
+enum {
+  PlaceRequested,   /* where the window asked to be */
+  PlaceCenter,      /* middle of the monitor */
+  PlaceParent,      /* middle of the parent of a transient */
+  PlacePointer,     /* under the pointer */
+  PlaceLeastOverlap /* covering other floating windows the least */
+}; /* floating placement */
+enum {
+  WidgetTags,
+  WidgetLtSymbol,
+  WidgetTitle, /* takes the space the other widgets leave */
+  WidgetStatus,
+  WidgetSystray
+}; /* bar widgets */
+enum { BarLeft, BarCenter, BarRight }; /* bar widget alignment */
 
 typedef union {
   int i;
@@ -133,18 +173,27 @@ typedef struct {
 } Button;
 
 typedef struct Monitor Monitor;
+typedef struct Pertag Pertag;
+typedef struct BspTrees BspTrees;
 typedef struct Client Client;
 struct Client {
   char name[256];
   float mina, maxa;
+  float cfact;
   int x, y, w, h;
   int oldx, oldy, oldw, oldh;
   int basew, baseh, incw, inch, maxw, maxh, minw, minh;
   int bw, oldbw;
+  int defbw; /* border width from the rules, smartborders can drop bw to 0 */
   unsigned int tags;
   int isfixed, isfloating, isurgent, neverfocus, oldstate, isfullscreen;
+  int issticky; /* visible on every tag of its monitor */
+  int isterminal, noswallow;
+  int placement; /* where the client goes when it floats */
+  pid_t pid;
   Client *next;
   Client *snext;
+  Client *swallowing; /* the terminal's own window while it shows a program */
   Monitor *mon;
   Window win;
 };
@@ -159,16 +208,31 @@ typedef struct {
 typedef struct {
   const char *symbol;
   void (*arrange)(Monitor *);
+  const char *flexlayout; /* layout string of rust_flextile */
 } Layout;
 
+typedef struct {
+  const char *output; /* NULL matches every output */
+  float minaspect;    /* width / height, 0 means no lower bound */
+  float maxaspect;    /* width / height, 0 means no upper bound */
+  int layout;         /* index into layouts */
+} MonitorRule;
+
 struct Monitor {
   char ltsymbol[16];
   float mfact;
   int nmaster;
+  int mastercols; /* master windows side by side instead of stacked */
+  unsigned int ltmods; /* layout modifiers, see LtMirrorH */
   int num;
   int by;             /* bar geometry */
   int mx, my, mw, mh; /* screen size */
   int wx, wy, ww, wh; /* window area  */
+  int gappih;         /* horizontal gap between windows */
+  int gappiv;         /* vertical gap between windows */
+  int gappoh;         /* horizontal gap to the screen edge */
+  int gappov;         /* vertical gap to the screen edge */
+  int enablegaps;
   unsigned int seltags;
   unsigned int sellt;
   unsigned int tagset[2];
@@ -177,20 +241,49 @@ struct Monitor {
   Client *clients;
   Client *sel;
   Client *stack;
+  BspTrees *bsp; /* split trees of the bsp layout, owned by the rust side */
   Monitor *next;
   Window barwin;
+  Window tabwin;
+  int tx, ty, tw; /* tab bar geometry, tw is 0 when hidden */
+  int mastersplit; /* where the layout put the master area, see SplitLeft */
+  int stacksplit;  /* how the layout shares the stack, see StackRows */
   const Layout *lt[2];
+  Pertag *pertag;
+  const MonitorRule *monrule; /* the monitor rule that matched last */
 };
 
+typedef struct {
+  int layout;  /* index into layouts, -1 means use the monitor layout */
+  float mfact; /* 0 means use mfact */
+  int nmaster; /* -1 means use nmaster */
+  int showbar; /* -1 means use showbar */
+} TagDefault;
+
 typedef struct {
   const char *class;
   const char *instance;
   const char *title;
   unsigned int tags;
   int isfloating;
+  int isterminal;
+  int noswallow;
   int monitor;
+  int bw;               /* border width, -1 means borderpx */
+  int placement;        /* floating placement, -1 means floatplacement */
+  const char *floatgeom; /* WxH+X+Y when floating, numbers can end in % */
 } Rule;
 
+typedef struct {
+  const char *name; /* used by togglescratch */
+  const void *cmd;  /* spawns the window, a rule gives it SPTAG(i) */
+} Scratchpad;
+
+typedef struct {
+  int widget; /* WidgetTags, WidgetLtSymbol, ... */
+  int align;  /* BarLeft, BarCenter or BarRight */
+} Widget;
+
 typedef struct Systray Systray;
 struct Systray {
   Window win;
@@ -224,6 +317,7 @@ void destroynotify(XEvent *e);
 
 // NOTE(patrik): Need work
 
+static void applymonrules(Monitor *m);
 static void applyrules(Client *c);
 static int applysizehints(Client *c, int *x, int *y, int *w, int *h,
                           int interact);
@@ -236,10 +330,12 @@ void clientmessage(XEvent *e);
 void configurerequest(XEvent *e);
 static Monitor *createmon(void);
 static Monitor *dirtomon(int dir);
+static void dragsplit(const Arg *arg);
 void enternotify(XEvent *e);
 void focus(Client *c);
 static void focusmon(const Arg *arg);
 static void focusstack(const Arg *arg);
+static pid_t getparentprocess(pid_t p);
 static Atom getatomprop(Client *c, Atom prop);
 static int getrootptr(int *x, int *y);
 long getstate(Window w);
@@ -248,12 +344,14 @@ static int gettextprop(Window w, Atom atom, char *text, unsigned int size);
 static void grabbuttons(Client *c, int focused);
 void grabkeys(void);
 static void incnmaster(const Arg *arg);
+static int isdescprocess(pid_t p, pid_t c);
 void keypress(XEvent *e);
 static void killclient(const Arg *arg);
 void manage(Window w, XWindowAttributes *wa);
 void maprequest(XEvent *e);
 void motionnotify(XEvent *e);
 static void movemouse(const Arg *arg);
+static char *outputname(Monitor *m);
 void pop(Client *);
 void propertynotify(XEvent *e);
 static void quit(const Arg *arg);
@@ -263,28 +361,36 @@ void resize(Client *c, int x, int y, int w, int h, int interact);
 static void resizeclient(Client *c, int x, int y, int w, int h);
 static void resizemouse(const Arg *arg);
 void resizerequest(XEvent *e);
-static void restack(Monitor *m);
+void restack(Monitor *m);
 static int sendevent(Window w, Atom proto, int m, long d0, long d1, long d2,
                      long d3, long d4);
 static void setclientstate(Client *c, long state);
 void setfocus(Client *c);
 static void setfullscreen(Client *c, int fullscreen);
 static void setlayout(const Arg *arg);
+static void setnetwmstate(Client *c);
 static void setmfact(const Arg *arg);
 static void setup(void);
+static void setsticky(Client *c, int sticky);
 static void seturgent(Client *c, int urg);
 static void showhide(Client *c);
 static void sigchld(int unused);
 static void spawn(const Arg *arg);
+static float splitfact(Monitor *m, int horiz, int x, int y);
+static void swallow(Client *p, Client *c);
+static Client *swallowingclient(Window w);
 static Monitor *systraytomon(Monitor *m);
 static void tag(const Arg *arg);
+static Client *termforwin(const Client *c);
 static void tagmon(const Arg *arg);
-static void togglebar(const Arg *arg);
+void togglebar(const Arg *arg);
 static void togglefloating(const Arg *arg);
+static void togglescratch(const Arg *arg);
+static void togglesticky(const Arg *arg);
 static void toggletag(const Arg *arg);
-static void toggleview(const Arg *arg);
 void unfocus(Client *c, int setfocus);
 static void unmanage(Client *c, int destroyed);
+static void unswallow(Client *c);
 void unmapnotify(XEvent *e);
 static void updatebarpos(Monitor *m);
 static void updatebars(void);
@@ -297,24 +403,24 @@ static void updatestatus(void);
 void updatesystray(void);
 static void updatesystrayicongeom(Client *i, int w, int h);
 static void updatesystrayiconstate(Client *i, XPropertyEvent *ev);
+static void updatetabwin(Monitor *m);
 static void updatetitle(Client *c);
 static void updatewindowtype(Client *c);
 static void updatewmhints(Client *c);
+static pid_t winpid(Window w);
 Monitor *wintomon(Window w);
 static Client *wintosystrayicon(Window w);
 static int xerrordummy(Display *dpy, XErrorEvent *ee);
 static void xinitvisual();
 
 /* variables */
-const BAR_ITEM_WIDTH = 40;
-
 static Systray *systray = NULL;
 static const char broken[] = "broken";
 char stext[256];
 static int screen;
 
 static int sw, sh; /* X display screen geometry width, height */
-int bh, blw = 0;   /* bar geometry */
+int bh;            /* bar geometry */
 int lrpad;         /* sum of left and right padding for text */
 static int (*xerrorxlib)(Display *, XErrorEvent *);
 static unsigned int numlockmask = 0;
@@ -353,18 +459,66 @@ static Colormap cmap;
 
 /* compile-time check if all tags fit into an unsigned int bit array. */
 struct NumTags {
-  char limitexceeded[LENGTH(tags) > 31 ? -1 : 1];
+  char limitexceeded[LENGTH(tags) + LENGTH(scratchpads) > 31 ? -1 : 1];
+};
+
+/* layout settings for every tag, slot 0 is used when all tags are viewed */
+struct Pertag {
+  unsigned int curtag, prevtag;              /* current and previous slot */
+  int nmasters[LENGTH(tags) + 1];            /* number of master windows */
+  float mfacts[LENGTH(tags) + 1];            /* size of the master area */
+  unsigned int sellts[LENGTH(tags) + 1];     /* selected layouts */
+  const Layout *ltidxs[LENGTH(tags) + 1][2]; /* layouts of every slot */
+  int showbars[LENGTH(tags) + 1];            /* bar visibility */
 };
 
 /* function implementations */
+void applymonrules(Monitor *m) {
+  const MonitorRule *r = NULL;
+  const Layout *l;
+  const TagDefault *td;
+  char *output;
+  float aspect;
+  unsigned int i;
+
+  if (m->mw <= 0 || m->mh <= 0)
+    return;
+  aspect = (float)m->mw / m->mh;
+  output = outputname(m);
+  for (i = 0; i < LENGTH(monrules); i++) {
+    r = &monrules[i];
+    if ((!r->output || (output && !strcmp(r->output, output))) &&
+        (r->minaspect <= 0 || aspect >= r->minaspect) &&
+        (r->maxaspect <= 0 || aspect <= r->maxaspect))
+      break;
+  }
+  if (output)
+    XFree(output);
+  /* the layouts picked since the rule last changed are kept */
+  if (i == LENGTH(monrules) || r == m->monrule)
+    return;
+  m->monrule = r;
+
+  /* tags with their own layout in tagdefaults keep it */
+  l = &layouts[r->layout % LENGTH(layouts)];
+  for (i = 0; i <= LENGTH(tags); i++) {
+    td = i < LENGTH(tagdefaults) ? &tagdefaults[i] : NULL;
+    if (!td || td->layout < 0)
+      m->pertag->ltidxs[i][m->pertag->sellts[i]] = l;
+  }
+  m->lt[m->sellt] = m->pertag->ltidxs[m->pertag->curtag][m->sellt];
+  strncpy(m->ltsymbol, m->lt[m->sellt]->symbol, sizeof m->ltsymbol);
+}
+
 void applyrules(Client *c) {
-  const char *class, *instance;
+  const char *class, *instance, *floatgeom = NULL;
   unsigned int i;
   const Rule *r;
   Monitor *m;
   XClassHint ch = {NULL, NULL};
 
-  /* rule matching */
+  /* rule matching, the flags come from the last matching rule, the tags
+   * add up and the other fields come from the last rule that sets them */
   c->isfloating = 0;
   c->tags = 0;
   XGetClassHint(dpy, c->win, &ch);
@@ -376,20 +530,32 @@ void applyrules(Client *c) {
     if ((!r->title || strstr(c->name, r->title)) &&
         (!r->class || strstr(class, r->class)) &&
         (!r->instance || strstr(instance, r->instance))) {
+      c->isterminal = r->isterminal;
+      c->noswallow = r->noswallow;
       c->isfloating = r->isfloating;
       c->tags |= r->tags;
+      if (r->bw >= 0)
+        c->defbw = r->bw;
+      if (r->placement >= 0)
+        c->placement = r->placement;
       for (m = mons; m && m->num != r->monitor; m = m->next)
         ;
       if (m)
         c->mon = m;
+      if (r->floatgeom)
+        floatgeom = r->floatgeom;
     }
   }
+  /* after the loop so the geometry sees the final border width */
+  if (c->isfloating && floatgeom && rust_float_geometry(c, floatgeom))
+    c->placement = PlaceRequested;
   if (ch.res_class)
     XFree(ch.res_class);
   if (ch.res_name)
     XFree(ch.res_name);
-  c->tags =
-      c->tags & TAGMASK ? c->tags & TAGMASK : c->mon->tagset[c->mon->seltags];
+  c->tags = c->tags & (TAGMASK | SPTAGMASK)
+                ? c->tags & (TAGMASK | SPTAGMASK)
+                : c->mon->tagset[c->mon->seltags] & TAGMASK;
 }
 
 int applysizehints(Client *c, int *x, int *y, int *w, int *h, int interact) {
@@ -471,14 +637,28 @@ void arrange(Monitor *m) {
 }
 
 void arrangemon(Monitor *m) {
+  Client *c;
+  int tx = m->tx, ty = m->ty, tw = m->tw;
+
   strncpy(m->ltsymbol, m->lt[m->sellt]->symbol, sizeof m->ltsymbol);
+  m->tw = 0; /* the tabbed layout shows the tab bar again */
+  /* smartborders only takes the border from tiled clients */
+  for (c = m->clients; c; c = c->next)
+    if (c->bw != c->defbw && !c->isfullscreen &&
+        (c->isfloating || !m->lt[m->sellt]->arrange)) {
+      c->bw = c->defbw;
+      XSetWindowBorderWidth(dpy, c->win, c->bw);
+      configure(c);
+    }
   if (m->lt[m->sellt]->arrange)
     m->lt[m->sellt]->arrange(m);
+  if (m->tw != tw || (m->tw && (m->tx != tx || m->ty != ty)))
+    updatetabwin(m);
 }
 
 void buttonpress(XEvent *e) {
-  unsigned int i, x, click;
-  Arg arg = {0};
+  unsigned int i, click;
+  Arg arg = {0}, block;
   Client *c;
   Monitor *m;
   XButtonPressedEvent *ev = &e->xbutton;
@@ -491,19 +671,10 @@ void buttonpress(XEvent *e) {
     focus(NULL);
   }
   if (ev->window == selmon->barwin) {
-    i = x = 0;
-    do
-      x += BAR_ITEM_WIDTH;
-    while (ev->x >= x && ++i < LENGTH(tags));
-    if (i < LENGTH(tags)) {
-      click = ClkTagBar;
-      arg.ui = 1 << i;
-    } else if (ev->x < x + blw) {
-      click = ClkLtSymbol;
-    } else if (ev->x > selmon->ww - (int)TEXTW(stext) - getsystraywidth())
-      click = ClkStatusText;
-    else
-      click = ClkWinTitle;
+    click = rust_bar_click(selmon, ev->x, &arg);
+  } else if (ev->window == selmon->tabwin) {
+    click = ClkTabBar;
+    arg.i = rust_tab_at(selmon, ev->x);
   } else if ((c = wintoclient(ev->window))) {
     focus(c);
     restack(selmon);
@@ -513,9 +684,17 @@ void buttonpress(XEvent *e) {
   for (i = 0; i < LENGTH(buttons); i++)
     if (click == buttons[i].click && buttons[i].func &&
         buttons[i].button == ev->button &&
-        CLEANMASK(buttons[i].mask) == CLEANMASK(ev->state))
-      buttons[i].func(
-          click == ClkTagBar && buttons[i].arg.i == 0 ? &arg : &buttons[i].arg);
+        CLEANMASK(buttons[i].mask) == CLEANMASK(ev->state)) {
+      if (click == ClkStatusBlock) {
+        /* the clicked block above the value of the binding */
+        block.ui = arg.ui << 8 | (buttons[i].arg.ui & 0xff);
+        buttons[i].func(&block);
+      } else
+        buttons[i].func((click == ClkTagBar || click == ClkTabBar) &&
+                                buttons[i].arg.i == 0
+                            ? &arg
+                            : &buttons[i].arg);
+    }
 }
 
 void checkotherwm(void) {
@@ -569,6 +748,10 @@ void cleanupmon(Monitor *mon) {
   }
   XUnmapWindow(dpy, mon->barwin);
   XDestroyWindow(dpy, mon->barwin);
+  XUnmapWindow(dpy, mon->tabwin);
+  XDestroyWindow(dpy, mon->tabwin);
+  rust_bsp_free(mon);
+  free(mon->pertag);
   free(mon);
 }
 
@@ -641,6 +824,11 @@ void clientmessage(XEvent *e) {
       setfullscreen(c, (cme->data.l[0] == 1 /* _NET_WM_STATE_ADD    */
                         || (cme->data.l[0] == 2 /* _NET_WM_STATE_TOGGLE */ &&
                             !c->isfullscreen)));
+    if (cme->data.l[1] == netatom[NetWMSticky] ||
+        cme->data.l[2] == netatom[NetWMSticky])
+      setsticky(c, (cme->data.l[0] == 1 /* _NET_WM_STATE_ADD    */
+                    || (cme->data.l[0] == 2 /* _NET_WM_STATE_TOGGLE */ &&
+                        !c->issticky)));
   } else if (cme->message_type == netatom[NetActiveWindow]) {
     if (c != selmon->sel && !c->isurgent)
       seturgent(c, 1);
@@ -728,16 +916,44 @@ void configurerequest(XEvent *e) {
 
 Monitor *createmon(void) {
   Monitor *m;
+  const TagDefault *td;
+  unsigned int i;
 
   m = ecalloc(1, sizeof(Monitor));
   m->tagset[0] = m->tagset[1] = 1;
   m->mfact = mfact;
   m->nmaster = nmaster;
+  m->mastercols = mastercols;
+  m->gappih = gappih;
+  m->gappiv = gappiv;
+  m->gappoh = gappoh;
+  m->gappov = gappov;
+  m->enablegaps = 1;
   m->showbar = showbar;
   m->topbar = topbar;
   m->lt[0] = &layouts[0];
   m->lt[1] = &layouts[1 % LENGTH(layouts)];
-  strncpy(m->ltsymbol, layouts[0].symbol, sizeof m->ltsymbol);
+
+  m->pertag = ecalloc(1, sizeof(Pertag));
+  m->pertag->curtag = m->pertag->prevtag = 1;
+  for (i = 0; i <= LENGTH(tags); i++) {
+    td = i < LENGTH(tagdefaults) ? &tagdefaults[i] : NULL;
+    m->pertag->nmasters[i] = td && td->nmaster >= 0 ? td->nmaster : m->nmaster;
+    m->pertag->mfacts[i] = td && td->mfact > 0 ? td->mfact : m->mfact;
+    m->pertag->sellts[i] = m->sellt;
+    m->pertag->ltidxs[i][0] = td && td->layout >= 0
+                                  ? &layouts[td->layout % LENGTH(layouts)]
+                                  : m->lt[0];
+    m->pertag->ltidxs[i][1] = m->lt[1];
+    m->pertag->showbars[i] = td && td->showbar >= 0 ? td->showbar : m->showbar;
+  }
+
+  /* the first tag is viewed from the start */
+  m->nmaster = m->pertag->nmasters[1];
+  m->mfact = m->pertag->mfacts[1];
+  m->lt[0] = m->pertag->ltidxs[1][0];
+  m->showbar = m->pertag->showbars[1];
+  strncpy(m->ltsymbol, m->lt[0]->symbol, sizeof m->ltsymbol);
   return m;
 }
 
@@ -747,6 +963,8 @@ void destroynotify(XEvent *e) {
 
   if ((c = wintoclient(ev->window)))
     unmanage(c, 1);
+  else if ((c = swallowingclient(ev->window)))
+    unmanage(c->swallowing, 1);
   else if ((c = wintosystrayicon(ev->window))) {
     removesystrayicon(c);
     rust_resize_bar_window(selmon);
@@ -769,6 +987,93 @@ Monitor *dirtomon(int dir) {
   return m;
 }
 
+void dragsplit(const Arg *arg) {
+  int x, y, n, horiz, nearstart, ismaster, dist, best = -1;
+  float f, total;
+  Client *c, *i, *a = NULL, *b = NULL;
+  XEvent ev;
+  Time lasttime = 0;
+
+  if (!(c = selmon->sel) || c->isfloating || c->isfullscreen ||
+      !selmon->lt[selmon->sellt]->arrange)
+    return;
+  if (!getrootptr(&x, &y))
+    return;
+  /* the edge of the client closest to the pointer is dragged, an edge to
+   * a client of the same area that the stack is split along moves the
+   * cfacts of both clients, any other edge is the master split */
+  horiz = MIN(x - c->x, c->x + WIDTH(c) - x) <
+          MIN(y - c->y, c->y + HEIGHT(c) - y);
+  nearstart = horiz ? x - c->x < c->x + WIDTH(c) - x
+                    : y - c->y < c->y + HEIGHT(c) - y;
+  if (selmon->stacksplit == (horiz ? StackColumns : StackRows)) {
+    for (n = 0, i = nexttiled(selmon->clients); i && i != c;
+         i = nexttiled(i->next), n++)
+      ;
+    ismaster = n < selmon->nmaster;
+    for (n = 0, i = nexttiled(selmon->clients); i;
+         i = nexttiled(i->next), n++) {
+      if (i == c || (n < selmon->nmaster) != ismaster)
+        continue;
+      /* only clients lined up with c on the side of the edge */
+      if (horiz ? i->y != c->y || HEIGHT(i) != HEIGHT(c) ||
+                      (i->x < c->x) != nearstart
+                : i->x != c->x || WIDTH(i) != WIDTH(c) ||
+                      (i->y < c->y) != nearstart)
+        continue;
+      dist = horiz ? abs(i->x - c->x) : abs(i->y - c->y);
+      if (best < 0 || dist < best) {
+        best = dist;
+        a = nearstart ? i : c;
+        b = nearstart ? c : i;
+      }
+    }
+  }
+  if (!a && splitfact(selmon, horiz, x, y) < 0)
+    return;
+  if (XGrabPointer(dpy, root, False, MOUSEMASK, GrabModeAsync, GrabModeAsync,
+                   None, cursor[CurResize]->cursor, CurrentTime) != GrabSuccess)
+    return;
+  do {
+    XMaskEvent(dpy, MOUSEMASK | ExposureMask | SubstructureRedirectMask, &ev);
+    switch (ev.type) {
+    case ConfigureRequest:
+    case Expose:
+    case MapRequest:
+      handler[ev.type](&ev);
+      break;
+    case MotionNotify:
+      if ((ev.xmotion.time - lasttime) <= (1000 / 60))
+        continue;
+      lasttime = ev.xmotion.time;
+
+      if (a) {
+        /* a is left of or above b */
+        n = horiz ? b->x + WIDTH(b) - a->x : b->y + HEIGHT(b) - a->y;
+        if (n <= 0)
+          break;
+        f = (float)(horiz ? ev.xmotion.x - a->x : ev.xmotion.y - a->y) / n;
+        total = a->cfact + b->cfact;
+        if (total * f < 0.25 || total * f > 4.0 || total * (1.0 - f) < 0.25 ||
+            total * (1.0 - f) > 4.0)
+          break;
+        a->cfact = total * f;
+        b->cfact = total - a->cfact;
+      } else {
+        f = splitfact(selmon, horiz, ev.xmotion.x, ev.xmotion.y);
+        if (f < 0.05 || f > 0.95)
+          break;
+        selmon->mfact = selmon->pertag->mfacts[selmon->pertag->curtag] = f;
+      }
+      arrange(selmon);
+      break;
+    }
+  } while (ev.type != ButtonRelease);
+  XUngrabPointer(dpy, CurrentTime);
+  while (XCheckMaskEvent(dpy, EnterWindowMask, &ev))
+    ;
+}
+
 void enternotify(XEvent *e) {
   Client *c;
   Monitor *m;
@@ -868,6 +1173,21 @@ void focusstack(const Arg *arg) {
   }
 }
 
+/* the parent of process p, 0 when it can't be read from /proc */
+pid_t getparentprocess(pid_t p) {
+  unsigned int v = 0;
+  FILE *f;
+  char buf[256];
+
+  snprintf(buf, sizeof(buf) - 1, "/proc/%u/stat", (unsigned)p);
+  if (!(f = fopen(buf, "r")))
+    return 0;
+  if (fscanf(f, "%*u %*s %*c %u", &v) != 1)
+    v = 0;
+  fclose(f);
+  return (pid_t)v;
+}
+
 Atom getatomprop(Client *c, Atom prop) {
   int di;
   unsigned long dl;
@@ -985,10 +1305,18 @@ void grabkeys(void) {
 }
 
 void incnmaster(const Arg *arg) {
-  selmon->nmaster = MAX(selmon->nmaster + arg->i, 0);
+  selmon->nmaster = selmon->pertag->nmasters[selmon->pertag->curtag] =
+      MAX(selmon->nmaster + arg->i, 0);
   arrange(selmon);
 }
 
+/* whether process c is a descendant of process p */
+int isdescprocess(pid_t p, pid_t c) {
+  while (p != c && c != 0)
+    c = getparentprocess(c);
+  return (int)c;
+}
+
 #ifdef XINERAMA
 static int isuniquegeom(XineramaScreenInfo *unique, size_t n,
                         XineramaScreenInfo *info) {
@@ -1029,18 +1357,22 @@ void killclient(const Arg *arg) {
 }
 
 void manage(Window w, XWindowAttributes *wa) {
-  Client *c, *t = NULL;
+  Client *c, *t = NULL, *term = NULL;
   Window trans = None;
   XWindowChanges wc;
 
   c = ecalloc(1, sizeof(Client));
   c->win = w;
+  c->pid = winpid(w);
   /* geometry */
   c->x = c->oldx = wa->x;
   c->y = c->oldy = wa->y;
   c->w = c->oldw = wa->width;
   c->h = c->oldh = wa->height;
   c->oldbw = wa->border_width;
+  c->defbw = borderpx;
+  c->placement = floatplacement;
+  c->cfact = 1.0;
 
   updatetitle(c);
   if (XGetTransientForHint(dpy, w, &trans) && (t = wintoclient(trans))) {
@@ -1049,6 +1381,7 @@ void manage(Window w, XWindowAttributes *wa) {
   } else {
     c->mon = selmon;
     applyrules(c);
+    term = termforwin(c);
   }
 
   if (c->x + WIDTH(c) > c->mon->mx + c->mon->mw)
@@ -1062,7 +1395,11 @@ void manage(Window w, XWindowAttributes *wa) {
               (c->x + (c->w / 2) < c->mon->wx + c->mon->ww))
                  ? bh
                  : c->mon->my);
-  c->bw = borderpx;
+  c->bw = c->defbw;
+  if (c->tags & SPTAGMASK) { /* scratchpads float in the middle */
+    c->isfloating = 1;
+    c->placement = PlaceCenter;
+  }
 
   wc.border_width = c->bw;
   XConfigureWindow(dpy, w, CWBorderWidth, &wc);
@@ -1077,8 +1414,10 @@ void manage(Window w, XWindowAttributes *wa) {
   grabbuttons(c, 0);
   if (!c->isfloating)
     c->isfloating = c->oldstate = trans != None || c->isfixed;
-  if (c->isfloating)
+  if (c->isfloating) {
+    rust_place_client(c, t, c->placement);
     XRaiseWindow(dpy, c->win);
+  }
   rust_attach(c);
   rust_attach_stack(c);
   XChangeProperty(dpy, root, netatom[NetClientList], XA_WINDOW, 32,
@@ -1091,6 +1430,8 @@ void manage(Window w, XWindowAttributes *wa) {
   c->mon->sel = c;
   arrange(c->mon);
   XMapWindow(dpy, c->win);
+  if (term)
+    swallow(term, c);
   focus(NULL);
 }
 
@@ -1200,6 +1541,24 @@ Client *nexttiled(Client *c) {
   return c;
 }
 
+/* name of the output showing m, NULL when unknown, free it with XFree */
+char *outputname(Monitor *m) {
+  char *name = NULL;
+#ifdef XRANDR
+  XRRMonitorInfo *info;
+  int i, n;
+
+  if (!(info = XRRGetMonitors(dpy, root, True, &n)))
+    return NULL;
+  for (i = 0; i < n && !name; i++)
+    if (info[i].x == m->mx && info[i].y == m->my && info[i].width == m->mw &&
+        info[i].height == m->mh)
+      name = XGetAtomName(dpy, info[i].name);
+  XRRFreeMonitors(info);
+#endif /* XRANDR */
+  return name;
+}
+
 void pop(Client *c) {
   rust_detach(c);
   rust_attach(c);
@@ -1385,6 +1744,10 @@ void restack(Monitor *m) {
   if (m->lt[m->sellt]->arrange) {
     wc.stack_mode = Below;
     wc.sibling = m->barwin;
+    if (m->tw) {
+      XConfigureWindow(dpy, m->tabwin, CWSibling | CWStackMode, &wc);
+      wc.sibling = m->tabwin;
+    }
     for (c = m->stack; c; c = c->snext)
       if (!c->isfloating && ISVISIBLE(c)) {
         XConfigureWindow(dpy, c->win, CWSibling | CWStackMode, &wc);
@@ -1448,10 +1811,8 @@ void setfocus(Client *c) {
 
 void setfullscreen(Client *c, int fullscreen) {
   if (fullscreen && !c->isfullscreen) {
-    XChangeProperty(dpy, c->win, netatom[NetWMState], XA_ATOM, 32,
-                    PropModeReplace, (unsigned char *)&netatom[NetWMFullscreen],
-                    1);
     c->isfullscreen = 1;
+    setnetwmstate(c);
     c->oldstate = c->isfloating;
     c->oldbw = c->bw;
     c->bw = 0;
@@ -1459,9 +1820,8 @@ void setfullscreen(Client *c, int fullscreen) {
     resizeclient(c, c->mon->mx, c->mon->my, c->mon->mw, c->mon->mh);
     XRaiseWindow(dpy, c->win);
   } else if (!fullscreen && c->isfullscreen) {
-    XChangeProperty(dpy, c->win, netatom[NetWMState], XA_ATOM, 32,
-                    PropModeReplace, (unsigned char *)0, 0);
     c->isfullscreen = 0;
+    setnetwmstate(c);
     c->isfloating = c->oldstate;
     c->bw = c->oldbw;
     c->x = c->oldx;
@@ -1473,11 +1833,26 @@ void setfullscreen(Client *c, int fullscreen) {
   }
 }
 
+void setnetwmstate(Client *c) {
+  Atom state[2];
+  int n = 0;
+
+  if (c->isfullscreen)
+    state[n++] = netatom[NetWMFullscreen];
+  if (c->issticky)
+    state[n++] = netatom[NetWMSticky];
+  XChangeProperty(dpy, c->win, netatom[NetWMState], XA_ATOM, 32,
+                  PropModeReplace, (unsigned char *)state, n);
+}
+
 void setlayout(const Arg *arg) {
+  Pertag *pt = selmon->pertag;
+
   if (!arg || !arg->v || arg->v != selmon->lt[selmon->sellt])
-    selmon->sellt ^= 1;
+    selmon->sellt = pt->sellts[pt->curtag] ^= 1;
   if (arg && arg->v)
-    selmon->lt[selmon->sellt] = (Layout *)arg->v;
+    selmon->lt[selmon->sellt] = pt->ltidxs[pt->curtag][selmon->sellt] =
+        (Layout *)arg->v;
   strncpy(selmon->ltsymbol, selmon->lt[selmon->sellt]->symbol,
           sizeof selmon->ltsymbol);
   if (selmon->sel)
@@ -1495,7 +1870,7 @@ void setmfact(const Arg *arg) {
   f = arg->f < 1.0 ? arg->f + selmon->mfact : arg->f - 1.0;
   if (f < 0.05 || f > 0.95)
     return;
-  selmon->mfact = f;
+  selmon->mfact = selmon->pertag->mfacts[selmon->pertag->curtag] = f;
   arrange(selmon);
 }
 
@@ -1518,6 +1893,7 @@ void setup(void) {
     die("no fonts could be loaded.");
   lrpad = drw->fonts->h;
   bh = drw->fonts->h;
+  rust_check_config(layouts, LENGTH(layouts), rules, LENGTH(rules));
   updategeom();
   /* init atoms */
   utf8string = XInternAtom(dpy, "UTF8_STRING", False);
@@ -1538,6 +1914,7 @@ void setup(void) {
   netatom[NetWMCheck] = XInternAtom(dpy, "_NET_SUPPORTING_WM_CHECK", False);
   netatom[NetWMFullscreen] =
       XInternAtom(dpy, "_NET_WM_STATE_FULLSCREEN", False);
+  netatom[NetWMSticky] = XInternAtom(dpy, "_NET_WM_STATE_STICKY", False);
   netatom[NetWMWindowType] = XInternAtom(dpy, "_NET_WM_WINDOW_TYPE", False);
   netatom[NetWMWindowTypeDialog] =
       XInternAtom(dpy, "_NET_WM_WINDOW_TYPE_DIALOG", False);
@@ -1581,6 +1958,15 @@ void setup(void) {
   focus(NULL);
 }
 
+void setsticky(Client *c, int sticky) {
+  if (!sticky == !c->issticky)
+    return;
+  c->issticky = sticky;
+  setnetwmstate(c);
+  focus(NULL);
+  arrange(c->mon);
+}
+
 void seturgent(Client *c, int urg) {
   XWMHints *wmh;
 
@@ -1630,6 +2016,67 @@ void spawn(const Arg *arg) {
   }
 }
 
+/* mfact that moves the master split of m to x when horiz is set and to y
+ * otherwise, -1 when the split can't be dragged along that axis */
+float splitfact(Monitor *m, int horiz, int x, int y) {
+  if (horiz && m->ww > 0) {
+    switch (m->mastersplit) {
+    case SplitLeft:
+      return (float)(x - m->wx) / m->ww;
+    case SplitRight:
+      return (float)(m->wx + m->ww - x) / m->ww;
+    case SplitCenterH:
+      return 2.0 * abs(x - m->wx - m->ww / 2) / m->ww;
+    }
+  } else if (!horiz && m->wh > 0) {
+    switch (m->mastersplit) {
+    case SplitTop:
+      return (float)(y - m->wy) / m->wh;
+    case SplitBottom:
+      return (float)(m->wy + m->wh - y) / m->wh;
+    case SplitCenterV:
+      return 2.0 * abs(y - m->wy - m->wh / 2) / m->wh;
+    }
+  }
+  return -1;
+}
+
+/* hide terminal p and show c in its place, keeping p's list positions */
+void swallow(Client *p, Client *c) {
+  Window w;
+
+  if (c->noswallow || c->isterminal)
+    return;
+  rust_detach(c);
+  rust_detach_stack(c);
+  setclientstate(c, WithdrawnState);
+  XUnmapWindow(dpy, p->win);
+
+  p->swallowing = c;
+  c->mon = p->mon;
+
+  w = p->win;
+  p->win = c->win;
+  c->win = w;
+  updatetitle(p);
+  XMoveResizeWindow(dpy, p->win, p->x, p->y, p->w, p->h);
+  arrange(p->mon);
+  configure(p);
+  updateclientlist();
+}
+
+/* the client whose swallowed terminal owns window w */
+Client *swallowingclient(Window w) {
+  Client *c;
+  Monitor *m;
+
+  for (m = mons; m; m = m->next)
+    for (c = m->clients; c; c = c->next)
+      if (c->swallowing && c->swallowing->win == w)
+        return c;
+  return NULL;
+}
+
 void tag(const Arg *arg) {
   if (selmon->sel && arg->ui & TAGMASK) {
     selmon->sel->tags = arg->ui & TAGMASK;
@@ -1638,6 +2085,21 @@ void tag(const Arg *arg) {
   }
 }
 
+/* the terminal c was started from, if it can swallow c */
+Client *termforwin(const Client *c) {
+  Client *p;
+  Monitor *m;
+
+  if (!c->pid || c->isterminal)
+    return NULL;
+  for (m = mons; m; m = m->next)
+    for (p = m->clients; p; p = p->next)
+      if (p->isterminal && !p->swallowing && p->pid &&
+          isdescprocess(p->pid, c->pid))
+        return p;
+  return NULL;
+}
+
 void tagmon(const Arg *arg) {
   if (!selmon->sel || !mons->next)
     return;
@@ -1647,7 +2109,8 @@ void tagmon(const Arg *arg) {
 void tile(Monitor *m) { rust_tile(m); }
 
 void togglebar(const Arg *arg) {
-  selmon->showbar = !selmon->showbar;
+  selmon->showbar = selmon->pertag->showbars[selmon->pertag->curtag] =
+      !selmon->showbar;
   updatebarpos(selmon);
   rust_resize_bar_window(selmon);
   if (showsystray) {
@@ -1676,6 +2139,52 @@ void togglefloating(const Arg *arg) {
   arrange(selmon);
 }
 
+void togglescratch(const Arg *arg) {
+  Client *c = NULL;
+  Monitor *m;
+  unsigned int i, tag;
+  Arg sparg;
+
+  for (i = 0; i < LENGTH(scratchpads) && strcmp(scratchpads[i].name, arg->v);
+       i++)
+    ;
+  if (i == LENGTH(scratchpads))
+    return;
+  tag = SPTAG(i);
+  for (m = mons; m && !c; m = m->next)
+    for (c = m->clients; c && !(c->tags & tag); c = c->next)
+      ;
+  if (!c) {
+    /* the rule puts the new window on the hidden tag, view it right away */
+    selmon->tagset[selmon->seltags] |= tag;
+    sparg.v = scratchpads[i].cmd;
+    spawn(&sparg);
+    return;
+  }
+  if (c->mon != selmon) {
+    rust_send_to_monitor(c, selmon);
+    c->tags = tag;
+    selmon->tagset[selmon->seltags] |= tag;
+  } else
+    selmon->tagset[selmon->seltags] ^= tag;
+  if (ISVISIBLE(c)) {
+    c->isfloating = 1;
+    c->x = selmon->wx + (selmon->ww - WIDTH(c)) / 2;
+    c->y = selmon->wy + (selmon->wh - HEIGHT(c)) / 2;
+  }
+  focus(NULL);
+  arrange(selmon);
+  if (ISVISIBLE(c)) {
+    focus(c);
+    restack(selmon);
+  }
+}
+
+void togglesticky(const Arg *arg) {
+  if (selmon->sel)
+    setsticky(selmon->sel, !selmon->sel->issticky);
+}
+
 void toggletag(const Arg *arg) {
   unsigned int newtags;
 
@@ -1689,17 +2198,6 @@ void toggletag(const Arg *arg) {
   }
 }
 
-void toggleview(const Arg *arg) {
-  unsigned int newtagset =
-      selmon->tagset[selmon->seltags] ^ (arg->ui & TAGMASK);
-
-  if (newtagset) {
-    selmon->tagset[selmon->seltags] = newtagset;
-    focus(NULL);
-    arrange(selmon);
-  }
-}
-
 void unfocus(Client *c, int setfocus) {
   if (!c)
     return;
@@ -1713,8 +2211,21 @@ void unfocus(Client *c, int setfocus) {
 
 void unmanage(Client *c, int destroyed) {
   Monitor *m = c->mon;
+  Client *s;
   XWindowChanges wc;
 
+  if (c->swallowing) {
+    unswallow(c);
+    return;
+  }
+  if ((s = swallowingclient(c->win))) {
+    free(s->swallowing);
+    s->swallowing = NULL;
+    arrange(m);
+    focus(NULL);
+    return;
+  }
+
   rust_detach(c);
   rust_detach_stack(c);
   if (!destroyed) {
@@ -1734,6 +2245,24 @@ void unmanage(Client *c, int destroyed) {
   arrange(m);
 }
 
+/* give c its terminal window back once the swallowed program is gone */
+void unswallow(Client *c) {
+  c->win = c->swallowing->win;
+
+  free(c->swallowing);
+  c->swallowing = NULL;
+
+  /* unfullscreen the client */
+  setfullscreen(c, 0);
+  updatetitle(c);
+  arrange(c->mon);
+  XMapWindow(dpy, c->win);
+  XMoveResizeWindow(dpy, c->win, c->x, c->y, c->w, c->h);
+  setclientstate(c, NormalState);
+  focus(NULL);
+  arrange(c->mon);
+}
+
 void unmapnotify(XEvent *e) {
   Client *c;
   XUnmapEvent *ev = &e->xunmap;
@@ -1776,6 +2305,14 @@ void updatebars(void) {
       XMapRaised(dpy, systray->win);
     XMapRaised(dpy, m->barwin);
     XSetClassHint(dpy, m->barwin, &ch);
+    /* the tab bar is mapped when the tabbed layout needs it */
+    m->tabwin = XCreateWindow(dpy, root, m->wx, m->wy, m->ww, bh, 0, depth,
+                              InputOutput, visual,
+                              CWOverrideRedirect | CWBackPixel | CWBorderPixel |
+                                  CWColormap | CWEventMask,
+                              &wa);
+    XDefineCursor(dpy, m->tabwin, cursor[CurNormal]->cursor);
+    XSetClassHint(dpy, m->tabwin, &ch);
   }
 }
 
@@ -1846,6 +2383,7 @@ int updategeom(void) {
           m->mw = m->ww = unique[i].width;
           m->mh = m->wh = unique[i].height;
           updatebarpos(m);
+          applymonrules(m);
         }
     } else { /* less monitors available nn < n */
       for (i = nn; i < n; i++) {
@@ -1875,6 +2413,7 @@ int updategeom(void) {
       mons->mw = mons->ww = sw;
       mons->mh = mons->wh = sh;
       updatebarpos(mons);
+      applymonrules(mons);
     }
   }
   if (dirty) {
@@ -1942,6 +2481,7 @@ void updatesizehints(Client *c) {
 void updatestatus(void) {
   if (!gettextprop(root, XA_WM_NAME, stext, sizeof(stext)))
     strcpy(stext, "nano-dwm-" VERSION);
+  rust_update_status();
   rust_draw_bar(selmon);
   updatesystray();
 }
@@ -2042,7 +2582,7 @@ void updatesystray(void) {
       i->mon = m;
   }
   w = w ? w + systrayspacing : 1;
-  x -= w;
+  x = m->wx + rust_systray_x(m);
   XMoveResizeWindow(dpy, systray->win, x, m->by, w, bh);
   wc.x = x;
   wc.y = m->by;
@@ -2061,6 +2601,28 @@ void updatesystray(void) {
   XSync(dpy, False);
 }
 
+void updatetabwin(Monitor *m) {
+  XWindowChanges wc;
+
+  if (!m->tabwin)
+    return;
+  if (!m->tw) {
+    XUnmapWindow(dpy, m->tabwin);
+    return;
+  }
+  /* right above the tiled clients, restack keeps it there */
+  wc.x = m->tx;
+  wc.y = m->ty;
+  wc.width = m->tw;
+  wc.height = bh;
+  wc.sibling = m->barwin;
+  wc.stack_mode = Below;
+  XConfigureWindow(dpy, m->tabwin,
+                   CWX | CWY | CWWidth | CWHeight | CWSibling | CWStackMode,
+                   &wc);
+  XMapWindow(dpy, m->tabwin);
+}
+
 void updatetitle(Client *c) {
   if (!gettextprop(c->win, netatom[NetWMName], c->name, sizeof c->name))
     gettextprop(c->win, XA_WM_NAME, c->name, sizeof c->name);
@@ -2074,6 +2636,8 @@ void updatewindowtype(Client *c) {
 
   if (state == netatom[NetWMFullscreen])
     setfullscreen(c, 1);
+  if (state == netatom[NetWMSticky])
+    c->issticky = 1;
   if (wtype == netatom[NetWMWindowTypeDialog])
     c->isfloating = 1;
 }
@@ -2107,6 +2671,24 @@ Client *wintosystrayicon(Window w) {
   return i;
 }
 
+pid_t winpid(Window w) {
+  pid_t result = 0;
+  Atom type;
+  int format;
+  unsigned long len, bytes;
+  unsigned char *prop;
+
+  if (XGetWindowProperty(dpy, w, XInternAtom(dpy, "_NET_WM_PID", False), 0,
+                         1, False, XA_CARDINAL, &type, &format, &len, &bytes,
+                         &prop) != Success ||
+      !prop)
+    return 0;
+  if (len == 1)
+    result = *(long *)prop;
+  XFree(prop);
+  return result;
+}
+
 Monitor *wintomon(Window w) {
   int x, y;
   Client *c;
@@ -2115,7 +2697,7 @@ Monitor *wintomon(Window w) {
   if (w == root && getrootptr(&x, &y))
     return recttomon(x, y, 1, 1);
   for (m = mons; m; m = m->next)
-    if (w == m->barwin)
+    if (w == m->barwin || w == m->tabwin)
       return m;
   if ((c = wintoclient(w)))
     return c->mon;
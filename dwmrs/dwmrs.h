@@ -8,13 +8,47 @@ void check_other_wm(Display *display);
 
 void rust_monocle(Monitor *monitor);
 void rust_tile(Monitor *monitor);
+void rust_column_master(Monitor *monitor);
+void rust_three_column(Monitor *monitor);
+void rust_flextile(Monitor *monitor);
+void rust_spiral(Monitor *monitor);
+void rust_dwindle(Monitor *monitor);
+void rust_centered_master(Monitor *monitor);
+void rust_centered_floating_master(Monitor *monitor);
+void rust_bstack(Monitor *monitor);
+void rust_bstack_horiz(Monitor *monitor);
+void rust_grid(Monitor *monitor);
+void rust_gapless_grid(Monitor *monitor);
+void rust_deck(Monitor *monitor);
+void rust_tabbed(Monitor *monitor);
+void rust_bsp(Monitor *monitor);
+
+void rust_bsp_free(Monitor *monitor);
+void rust_bsp_preselect(const Arg *arg);
+void rust_bsp_rotate(const Arg *arg);
+void rust_bsp_flip(const Arg *arg);
+void rust_bsp_balance(const Arg *arg);
+void rust_bsp_resize(const Arg *arg);
+void rust_bsp_swap(const Arg *arg);
+
+void rust_place_client(Client *client, const Client *parent, int placement);
+int rust_float_geometry(Client *client, const char *geometry);
+
+int rust_tab_at(Monitor *monitor, int x);
+void rust_focus_tab(const Arg *arg);
 
 void rust_test(Drw *drw, Clr *scheme);
 
 void rust_resize_bar_window(Monitor *monitor);
 
+void rust_check_config(const Layout *layouts, int nlayouts, const Rule *rules,
+                       int nrules);
+void rust_update_status();
 void rust_draw_bar(Monitor *monitor);
 void rust_draw_bars();
+int rust_bar_click(Monitor *monitor, int x, Arg *arg);
+int rust_systray_x(Monitor *monitor);
+void rust_signal_status(const Arg *arg);
 
 void rust_attach(Client *client);
 void rust_attach_stack(Client *client);
@@ -30,7 +64,17 @@ void rust_focus_in_event(XEvent *event);
 void rust_mapping_notify_event(XEvent *event);
 
 void rust_view(const Arg *arg);
+void rust_toggle_view(const Arg *arg);
 void rust_zoom(const Arg *arg);
+void rust_set_cfact(const Arg *arg);
+void rust_toggle_master_columns(const Arg *arg);
+void rust_toggle_layout_modifier(const Arg *arg);
+
+void rust_inc_gaps(const Arg *arg);
+void rust_inc_outer_gaps(const Arg *arg);
+void rust_inc_inner_gaps(const Arg *arg);
+void rust_toggle_gaps(const Arg *arg);
+void rust_default_gaps(const Arg *arg);
 
 void rust_run();
 void rust_scan();
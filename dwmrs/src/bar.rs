@@ -0,0 +1,604 @@
+//! Modular bar
+//!
+//! The bar is a row of widgets. Every widget says how wide it wants to be,
+//! draws itself into the slot it gets and tells which click region a click
+//! inside of it hit. Which widgets there are, their order and the side of
+//! the bar they sit on comes from `barwidgets` in config.h.
+
+use std::collections::BTreeMap;
+use std::ffi::{c_char, c_int, c_uint, CStr, CString};
+use std::sync::Mutex;
+
+use x11::xft::XftColor;
+
+use crate::layout::Rect;
+use crate::status::{self, Markup};
+use crate::{
+    bh, drw, drw_clr_create, drw_fontset_getwidth, drw_rect, drw_setscheme,
+    drw_text, getsystraywidth, lrpad, scheme, selmon, showsystray, stext,
+    systraytomon, Monitor, TAGS,
+};
+
+const CLK_TAG_BAR: c_int = 0;
+const CLK_LT_SYMBOL: c_int = 1;
+const CLK_STATUS_TEXT: c_int = 2;
+const CLK_WIN_TITLE: c_int = 3;
+const CLK_STATUS_BLOCK: c_int = 7;
+
+/// Width of a tag including its arrow
+const TAG_WIDTH: i32 = 40;
+/// Extra room in front of the first tag so it doesn't touch the screen edge
+const FIRST_TAG_INDENT: i32 = 5;
+/// Space between the left side of a tag and its name
+const TAG_PADDING: i32 = 8;
+/// Powerline arrow drawn between the tags
+const TAG_ARROW: &str = "\u{e0b0}";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    pub fn from_index(index: i32) -> Option<Self> {
+        match index {
+            0 => Some(Align::Left),
+            1 => Some(Align::Center),
+            2 => Some(Align::Right),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Width {
+    Fixed(i32),
+    /// Takes the space the fixed widgets leave, shared with the other
+    /// filling widgets
+    Fill,
+}
+
+/// The click region a click hit, `arg` is the tag or the status block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Click {
+    pub region: c_int,
+    pub arg: Option<u32>,
+}
+
+impl Click {
+    fn region(region: c_int) -> Self {
+        Self { region, arg: None }
+    }
+}
+
+/// The bar a widget is drawn into
+pub struct Bar<'a> {
+    pub monitor: &'a Monitor,
+    pub ptr: *mut Monitor,
+}
+
+pub trait BarWidget {
+    fn measure(&self, bar: &Bar) -> Width;
+
+    /// Draws the widget into the slot starting at `x`
+    fn draw(&self, bar: &Bar, x: i32, width: i32);
+
+    /// `x` is relative to the start of the widget
+    fn click(&self, bar: &Bar, x: i32, width: i32) -> Option<Click>;
+}
+
+pub fn widget(index: i32) -> Option<&'static dyn BarWidget> {
+    match index {
+        0 => Some(&Tags),
+        1 => Some(&LayoutSymbol),
+        2 => Some(&WindowTitle),
+        3 => Some(&StatusText),
+        4 => Some(&Systray),
+        _ => None,
+    }
+}
+
+/// Places widgets with the given widths and alignments in a bar `width`
+/// wide and returns the x and width of each one. Left widgets are packed
+/// from the left edge, right widgets against the right edge and center
+/// widgets in the middle of the room left between them.
+pub fn arrange(widgets: &[(Width, Align)], width: i32) -> Vec<(i32, i32)> {
+    let fixed = widgets
+        .iter()
+        .map(|(width, _)| match width {
+            Width::Fixed(width) => *width,
+            Width::Fill => 0,
+        })
+        .sum::<i32>();
+    let fills = widgets
+        .iter()
+        .filter(|(width, _)| *width == Width::Fill)
+        .count() as i32;
+
+    let free = (width - fixed).max(0);
+    let mut extra = if fills > 0 { free % fills } else { 0 };
+    let widths = widgets
+        .iter()
+        .map(|(width, _)| match width {
+            Width::Fixed(width) => *width,
+            Width::Fill => {
+                let share = free / fills + extra;
+                extra = 0;
+                share
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let total = |align: Align| {
+        widgets
+            .iter()
+            .zip(&widths)
+            .filter(|((_, a), _)| *a == align)
+            .map(|(_, width)| width)
+            .sum::<i32>()
+    };
+    let (left, center, right) = (
+        total(Align::Left),
+        total(Align::Center),
+        total(Align::Right),
+    );
+
+    let mut left_x = 0;
+    let mut center_x =
+        ((width - center) / 2).min(width - right - center).max(left);
+    let mut right_x = width - right;
+
+    widgets
+        .iter()
+        .zip(widths)
+        .map(|((_, align), width)| {
+            let x = match align {
+                Align::Left => &mut left_x,
+                Align::Center => &mut center_x,
+                Align::Right => &mut right_x,
+            };
+            let slot = (*x, width);
+            *x += width;
+            slot
+        })
+        .collect()
+}
+
+/// The colors of the status markup by name and transparency, a status
+/// program only uses a few so they are created once and never freed
+static COLORS: Mutex<BTreeMap<(String, c_uint), XftColor>> =
+    Mutex::new(BTreeMap::new());
+
+/// Replaces `color` with a `#rrggbb` color of the same transparency
+unsafe fn set_color(color: &mut XftColor, name: &str) {
+    let alpha = (color.pixel >> 24) as c_uint;
+    let mut colors = COLORS.lock().unwrap();
+    *color = *colors.entry((name.to_string(), alpha)).or_insert_with(|| {
+        let mut created = *color;
+        let name = CString::new(name).unwrap_or_default();
+        drw_clr_create(drw, &mut created, name.as_ptr(), alpha);
+        created
+    });
+}
+
+/// The part of `rect` inside `area`, `None` when they don't overlap
+fn clip(rect: Rect, area: Rect) -> Option<Rect> {
+    let x = rect.x.max(area.x);
+    let y = rect.y.max(area.y);
+    let right = (rect.x + rect.width).min(area.x + area.width);
+    let bottom = (rect.y + rect.height).min(area.y + area.height);
+    (right > x && bottom > y).then(|| Rect::new(x, y, right - x, bottom - y))
+}
+
+fn text_width(text: *const c_char) -> i32 {
+    unsafe { drw_fontset_getwidth(drw, text) as i32 }
+}
+
+/// Draws `text` over the whole slot with the normal or selected scheme
+fn draw_text(x: i32, width: i32, text: *const c_char, selected: bool) {
+    unsafe {
+        drw_setscheme(drw, *scheme.offset(selected as isize));
+        drw_text(
+            drw,
+            x,
+            0,
+            width as u32,
+            bh as u32,
+            (lrpad / 2) as u32,
+            text,
+            0,
+        );
+    }
+}
+
+pub struct Tags;
+
+impl Tags {
+    fn tag_width(index: usize) -> i32 {
+        if index == 0 {
+            TAG_WIDTH + FIRST_TAG_INDENT
+        } else {
+            TAG_WIDTH
+        }
+    }
+}
+
+impl BarWidget for Tags {
+    fn measure(&self, _bar: &Bar) -> Width {
+        Width::Fixed((0..TAGS.len()).map(Tags::tag_width).sum())
+    }
+
+    fn draw(&self, bar: &Bar, x: i32, _width: i32) {
+        let monitor = bar.monitor;
+
+        let mut urgent = 0;
+        let mut client = monitor.clients;
+        while !client.is_null() {
+            unsafe {
+                if (*client).is_urgent > 0 {
+                    urgent |= (*client).tags;
+                }
+                client = (*client).next;
+            }
+        }
+
+        let arrow = CString::new(TAG_ARROW).unwrap();
+        let arrow_width = text_width(arrow.as_ptr());
+        let tagset = monitor.tagset[monitor.seltags as usize];
+
+        let mut x = x;
+        for (index, tag) in TAGS.iter().enumerate() {
+            let tag = CString::new(*tag).unwrap();
+            let selected = tagset & (1 << index) > 0;
+            let next_selected =
+                index + 1 < TAGS.len() && tagset & (1 << (index + 1)) > 0;
+
+            unsafe {
+                let normal = *scheme.offset(0);
+                let sel = *scheme.offset(1);
+
+                // The arrow points from the tag into the next one, so it
+                // takes the background of both
+                let mut arrow_scheme = [
+                    *(if selected { sel } else { normal }).offset(1),
+                    *(if next_selected && !selected {
+                        sel
+                    } else {
+                        normal
+                    })
+                    .offset(1),
+                    *normal.offset(2),
+                ];
+
+                let text_width = Tags::tag_width(index) - arrow_width;
+                let padding = if index == 0 {
+                    TAG_PADDING + FIRST_TAG_INDENT
+                } else {
+                    TAG_PADDING
+                };
+
+                drw_setscheme(drw, if selected { sel } else { normal });
+                drw_text(
+                    drw,
+                    x,
+                    0,
+                    text_width as u32,
+                    bh as u32,
+                    padding as u32,
+                    tag.as_ptr(),
+                    (urgent & 1 << index) as i32,
+                );
+
+                drw_setscheme(drw, arrow_scheme.as_mut_ptr());
+                drw_text(
+                    drw,
+                    x + text_width,
+                    0,
+                    arrow_width as u32,
+                    bh as u32,
+                    0,
+                    arrow.as_ptr(),
+                    0,
+                );
+            }
+
+            x += Tags::tag_width(index);
+        }
+    }
+
+    fn click(&self, _bar: &Bar, x: i32, _width: i32) -> Option<Click> {
+        let mut end = 0;
+        (0..TAGS.len())
+            .position(|index| {
+                end += Tags::tag_width(index);
+                x < end
+            })
+            .map(|index| Click {
+                region: CLK_TAG_BAR,
+                arg: Some(1 << index),
+            })
+    }
+}
+
+pub struct LayoutSymbol;
+
+impl BarWidget for LayoutSymbol {
+    fn measure(&self, bar: &Bar) -> Width {
+        let symbol = bar.monitor.ltsymbol.as_ptr() as *const c_char;
+        Width::Fixed(text_width(symbol) + unsafe { lrpad })
+    }
+
+    fn draw(&self, bar: &Bar, x: i32, width: i32) {
+        let symbol = bar.monitor.ltsymbol.as_ptr() as *const c_char;
+        draw_text(x, width, symbol, false);
+    }
+
+    fn click(&self, _bar: &Bar, _x: i32, _width: i32) -> Option<Click> {
+        Some(Click::region(CLK_LT_SYMBOL))
+    }
+}
+
+pub struct WindowTitle;
+
+impl BarWidget for WindowTitle {
+    fn measure(&self, _bar: &Bar) -> Width {
+        Width::Fill
+    }
+
+    fn draw(&self, bar: &Bar, x: i32, width: i32) {
+        if width <= unsafe { bh } {
+            return;
+        }
+
+        let sel = bar.monitor.sel;
+        if sel.is_null() {
+            unsafe {
+                drw_setscheme(drw, *scheme.offset(0));
+                drw_rect(drw, x, 0, width, bh, 1, 1);
+            }
+            return;
+        }
+
+        unsafe {
+            let sel = &*sel;
+            draw_text(x, width, sel.name.as_ptr(), false);
+
+            let font_height = (*(*drw).fonts).height as i32;
+            let boxs = font_height / 9;
+            let boxw = font_height / 6 + 2;
+            if sel.is_floating > 0 {
+                drw_rect(drw, x + boxs, boxs, boxw, boxw, sel.is_fixed, 0);
+            }
+            if sel.is_sticky > 0 {
+                // Filled box in the bottom corner, below the floating box
+                drw_rect(drw, x + boxs, bh - boxs - boxw, boxw, boxw, 1, 0);
+            }
+        }
+    }
+
+    fn click(&self, _bar: &Bar, _x: i32, _width: i32) -> Option<Click> {
+        Some(Click::region(CLK_WIN_TITLE))
+    }
+}
+
+/// The status text split into its markup
+struct Status {
+    /// Every piece with how far it moves the position
+    pieces: Vec<(Markup, i32)>,
+    /// Includes the rectangles that stick out past the end of the text
+    width: i32,
+}
+
+static STATUS: Mutex<Status> = Mutex::new(Status {
+    pieces: Vec::new(),
+    width: 0,
+});
+
+/// Parses and measures the status text, has to be called whenever `stext`
+/// changes
+pub unsafe fn update_status() {
+    let status = CStr::from_ptr(stext.as_ptr()).to_string_lossy();
+    let pieces = status::parse(&status)
+        .into_iter()
+        .map(|markup| {
+            let width = match &markup {
+                Markup::Text(text) => {
+                    let text = CString::new(text.as_str()).unwrap_or_default();
+                    text_width(text.as_ptr())
+                }
+                Markup::Space(space) => *space,
+                _ => 0,
+            };
+            (markup, width)
+        })
+        .collect::<Vec<_>>();
+
+    let mut position = 0;
+    let mut width = 0;
+    for (markup, advance) in &pieces {
+        if let Markup::Rect(rect) = markup {
+            width = width.max(position + rect.x + rect.width);
+        }
+        position += advance;
+        width = width.max(position);
+    }
+
+    *STATUS.lock().unwrap() = Status { pieces, width };
+}
+
+/// The status text with its markup, only shown on the selected monitor
+pub struct StatusText;
+
+impl BarWidget for StatusText {
+    fn measure(&self, bar: &Bar) -> Width {
+        if bar.ptr != unsafe { selmon } {
+            return Width::Fixed(0);
+        }
+
+        let width = STATUS.lock().unwrap().width;
+        Width::Fixed(width + unsafe { lrpad })
+    }
+
+    fn draw(&self, _bar: &Bar, x: i32, width: i32) {
+        let status = STATUS.lock().unwrap();
+        let slot = Rect::new(x, 0, width, unsafe { bh });
+        let end = x + width;
+
+        unsafe {
+            let normal = *scheme.offset(0);
+            let bar_colors = [*normal, *normal.offset(1), *normal.offset(2)];
+            let mut colors = bar_colors;
+
+            drw_setscheme(drw, normal);
+            drw_rect(drw, x, 0, width, bh, 1, 1);
+
+            let mut x = x + lrpad / 2;
+            for (markup, measured) in &status.pieces {
+                match markup {
+                    Markup::Text(text) => {
+                        let text =
+                            CString::new(text.as_str()).unwrap_or_default();
+                        let w = (*measured).min(end - x);
+                        if w > 0 {
+                            drw_setscheme(drw, colors.as_mut_ptr());
+                            drw_text(
+                                drw,
+                                x,
+                                0,
+                                w as u32,
+                                bh as u32,
+                                0,
+                                text.as_ptr(),
+                                0,
+                            );
+                            x += w;
+                        }
+                    }
+                    Markup::Foreground(color) => {
+                        set_color(&mut colors[0], color)
+                    }
+                    Markup::Background(color) => {
+                        set_color(&mut colors[1], color)
+                    }
+                    Markup::Rect(rect) => {
+                        let rect = Rect::new(
+                            x + rect.x,
+                            rect.y,
+                            rect.width,
+                            rect.height,
+                        );
+                        if let Some(rect) = clip(rect, slot) {
+                            drw_setscheme(drw, colors.as_mut_ptr());
+                            drw_rect(
+                                drw,
+                                rect.x,
+                                rect.y,
+                                rect.width,
+                                rect.height,
+                                1,
+                                0,
+                            );
+                        }
+                    }
+                    Markup::Space(space) => x += *space,
+                    Markup::Reset => colors = bar_colors,
+                    Markup::Block(_) => {}
+                }
+            }
+        }
+    }
+
+    fn click(&self, _bar: &Bar, x: i32, _width: i32) -> Option<Click> {
+        let mut position = unsafe { lrpad } / 2;
+        let mut block = 0;
+        for (markup, width) in &STATUS.lock().unwrap().pieces {
+            match markup {
+                Markup::Block(_) if position > x => break,
+                Markup::Block(number) => block = *number,
+                _ => position += width,
+            }
+        }
+
+        Some(match block {
+            0 => Click::region(CLK_STATUS_TEXT),
+            block => Click {
+                region: CLK_STATUS_BLOCK,
+                arg: Some(block.into()),
+            },
+        })
+    }
+}
+
+/// Room for the systray window, which `updatesystray` moves on top of it
+pub struct Systray;
+
+impl BarWidget for Systray {
+    fn measure(&self, bar: &Bar) -> Width {
+        unsafe {
+            if showsystray == 0 || systraytomon(bar.ptr) != bar.ptr {
+                return Width::Fixed(0);
+            }
+
+            Width::Fixed(getsystraywidth() as i32)
+        }
+    }
+
+    fn draw(&self, _bar: &Bar, x: i32, width: i32) {
+        unsafe {
+            drw_setscheme(drw, *scheme.offset(0));
+            drw_rect(drw, x, 0, width, bh, 1, 1);
+        }
+    }
+
+    fn click(&self, _bar: &Bar, _x: i32, _width: i32) -> Option<Click> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrange_packs_the_sides_and_shares_the_rest() {
+        let widgets = [
+            (Width::Fixed(100), Align::Left),
+            (Width::Fill, Align::Left),
+            (Width::Fixed(50), Align::Right),
+            (Width::Fixed(30), Align::Left),
+        ];
+        assert_eq!(
+            arrange(&widgets, 1000),
+            [(0, 100), (100, 820), (950, 50), (920, 30)]
+        );
+    }
+
+    #[test]
+    fn arrange_centers_between_the_sides() {
+        let widgets = [
+            (Width::Fixed(100), Align::Left),
+            (Width::Fixed(200), Align::Center),
+            (Width::Fixed(500), Align::Right),
+        ];
+        // The middle of the bar would overlap the right side
+        assert_eq!(
+            arrange(&widgets, 1000),
+            [(0, 100), (300, 200), (500, 500)]
+        );
+
+        let fills = [(Width::Fill, Align::Left), (Width::Fill, Align::Right)];
+        assert_eq!(arrange(&fills, 101), [(0, 51), (51, 50)]);
+    }
+
+    #[test]
+    fn clip_keeps_the_part_inside_the_area() {
+        let area = Rect::new(100, 0, 50, 20);
+        assert_eq!(
+            clip(Rect::new(90, 10, 30, 30), area),
+            Some(Rect::new(100, 10, 20, 10))
+        );
+        assert_eq!(clip(Rect::new(150, 0, 10, 10), area), None);
+    }
+}
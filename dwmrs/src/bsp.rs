@@ -0,0 +1,631 @@
+//! Binary space partitioning layout
+//!
+//! Every tag keeps a tree of splits where the leaves are the clients. New
+//! clients split the focused leaf, either in the preselected direction or
+//! along the longest side of the leaf. Like the other layouts nothing in
+//! here touches X, clients are only known by their id.
+
+use crate::layout::{Layout, LayoutParams, Rect, TiledClient, Transform};
+
+const MIN_RATIO: f32 = 0.1;
+const MAX_RATIO: f32 = 0.9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Split {
+    /// The children are placed next to each other
+    Columns,
+    /// The children are placed on top of each other
+    Rows,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn from_index(index: i32) -> Option<Self> {
+        match index {
+            0 => Some(Direction::Left),
+            1 => Some(Direction::Right),
+            2 => Some(Direction::Up),
+            3 => Some(Direction::Down),
+            _ => None,
+        }
+    }
+
+    /// Where the direction `self` in the tree points to once `transform`
+    /// is applied
+    pub fn on_screen(self, transform: Transform) -> Self {
+        let direction = if transform.rotate {
+            self.transposed()
+        } else {
+            self
+        };
+        direction.mirrored(transform)
+    }
+
+    /// The direction in the tree that ends up pointing to `self` once
+    /// `transform` is applied
+    pub fn in_tree(self, transform: Transform) -> Self {
+        let direction = self.mirrored(transform);
+        if transform.rotate {
+            direction.transposed()
+        } else {
+            direction
+        }
+    }
+
+    fn mirrored(self, transform: Transform) -> Self {
+        match self {
+            Direction::Left if transform.mirror_h => Direction::Right,
+            Direction::Right if transform.mirror_h => Direction::Left,
+            Direction::Up if transform.mirror_v => Direction::Down,
+            Direction::Down if transform.mirror_v => Direction::Up,
+            direction => direction,
+        }
+    }
+
+    fn transposed(self) -> Self {
+        match self {
+            Direction::Left => Direction::Up,
+            Direction::Right => Direction::Down,
+            Direction::Up => Direction::Left,
+            Direction::Down => Direction::Right,
+        }
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            Direction::Left => '<',
+            Direction::Right => '>',
+            Direction::Up => '^',
+            Direction::Down => 'v',
+        }
+    }
+
+    fn split(self) -> Split {
+        match self {
+            Direction::Left | Direction::Right => Split::Columns,
+            Direction::Up | Direction::Down => Split::Rows,
+        }
+    }
+
+    /// The new client goes into the first child
+    fn is_first(self) -> bool {
+        matches!(self, Direction::Left | Direction::Up)
+    }
+}
+
+/// Where the next client should go
+#[derive(Clone, Copy, Debug)]
+pub struct Preselection {
+    pub direction: Direction,
+    /// How much of the split the first child gets
+    pub ratio: f32,
+}
+
+#[derive(Debug)]
+enum Node {
+    Leaf(u64),
+    Split {
+        split: Split,
+        ratio: f32,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+impl Node {
+    fn contains(&self, id: u64) -> bool {
+        match self {
+            Node::Leaf(leaf) => *leaf == id,
+            Node::Split { first, second, .. } => {
+                first.contains(id) || second.contains(id)
+            }
+        }
+    }
+
+    fn leaves(&self, result: &mut Vec<u64>) {
+        match self {
+            Node::Leaf(leaf) => result.push(*leaf),
+            Node::Split { first, second, .. } => {
+                first.leaves(result);
+                second.leaves(result);
+            }
+        }
+    }
+
+    fn leaf_count(&self) -> usize {
+        match self {
+            Node::Leaf(_) => 1,
+            Node::Split { first, second, .. } => {
+                first.leaf_count() + second.leaf_count()
+            }
+        }
+    }
+
+    fn leaf_mut(&mut self, id: u64) -> Option<&mut Node> {
+        match self {
+            Node::Leaf(leaf) if *leaf == id => Some(self),
+            Node::Leaf(_) => None,
+            Node::Split { first, second, .. } => match first.leaf_mut(id) {
+                Some(node) => Some(node),
+                None => second.leaf_mut(id),
+            },
+        }
+    }
+
+    /// The split that has the leaf `id` as one of its children
+    fn parent_mut(&mut self, id: u64) -> Option<&mut Node> {
+        let Node::Split { first, second, .. } = self else {
+            return None;
+        };
+
+        let is_parent = matches!(**first, Node::Leaf(leaf) if leaf == id) ||
+            matches!(**second, Node::Leaf(leaf) if leaf == id);
+        if is_parent {
+            return Some(self);
+        }
+
+        // Reborrow through the match again so the borrow checker can see
+        // that only one of the children is borrowed at a time
+        let Node::Split { first, second, .. } = self else {
+            unreachable!();
+        };
+
+        if first.contains(id) {
+            first.parent_mut(id)
+        } else {
+            second.parent_mut(id)
+        }
+    }
+
+    fn arrange(
+        &self,
+        area: Rect,
+        gaps: (i32, i32),
+        result: &mut Vec<(u64, Rect)>,
+    ) {
+        match self {
+            Node::Leaf(leaf) => result.push((*leaf, area)),
+            Node::Split {
+                split,
+                ratio,
+                first,
+                second,
+            } => {
+                let (a, b) = split_rect(area, *split, *ratio, gaps);
+                first.arrange(a, gaps, result);
+                second.arrange(b, gaps, result);
+            }
+        }
+    }
+
+    fn balance(&mut self) {
+        if let Node::Split {
+            ratio,
+            first,
+            second,
+            ..
+        } = self
+        {
+            first.balance();
+            second.balance();
+
+            let first_count = first.leaf_count() as f32;
+            let second_count = second.leaf_count() as f32;
+            *ratio = first_count / (first_count + second_count);
+        }
+    }
+
+    fn swap_ids(&mut self, a: u64, b: u64) {
+        match self {
+            Node::Leaf(leaf) if *leaf == a => *leaf = b,
+            Node::Leaf(leaf) if *leaf == b => *leaf = a,
+            Node::Leaf(_) => {}
+            Node::Split { first, second, .. } => {
+                first.swap_ids(a, b);
+                second.swap_ids(a, b);
+            }
+        }
+    }
+}
+
+/// Splits `area` into the rectangles of the two children of a split,
+/// `gaps` are the horizontal and vertical inner gaps
+fn split_rect(
+    area: Rect,
+    split: Split,
+    ratio: f32,
+    gaps: (i32, i32),
+) -> (Rect, Rect) {
+    match split {
+        Split::Columns => {
+            let width = ((area.width - gaps.0) as f32 * ratio) as i32;
+            area.split_h(width, gaps.0)
+        }
+        Split::Rows => {
+            let height = ((area.height - gaps.1) as f32 * ratio) as i32;
+            area.split_v(height, gaps.1)
+        }
+    }
+}
+
+/// The split tree of one tag
+#[derive(Debug, Default)]
+pub struct Tree {
+    root: Option<Node>,
+    preselection: Option<Preselection>,
+}
+
+impl Tree {
+    pub fn contains(&self, id: u64) -> bool {
+        self.root.as_ref().is_some_and(|root| root.contains(id))
+    }
+
+    pub fn leaves(&self) -> Vec<u64> {
+        let mut result = Vec::new();
+        if let Some(root) = &self.root {
+            root.leaves(&mut result);
+        }
+        result
+    }
+
+    /// Where every client goes when the tree fills `area`
+    pub fn rects(&self, area: Rect, gaps: (i32, i32)) -> Vec<(u64, Rect)> {
+        let mut result = Vec::new();
+        if let Some(root) = &self.root {
+            root.arrange(area, gaps, &mut result);
+        }
+        result
+    }
+
+    pub fn preselection(&self) -> Option<Preselection> {
+        self.preselection
+    }
+
+    pub fn preselect(&mut self, preselection: Option<Preselection>) {
+        self.preselection = preselection;
+    }
+
+    /// Inserts `id` by splitting the leaf of `focused`, or the last leaf if
+    /// `focused` isn't in the tree. `area` is used to split along the
+    /// longest side when nothing is preselected.
+    pub fn insert(
+        &mut self,
+        id: u64,
+        focused: Option<u64>,
+        area: Rect,
+        gaps: (i32, i32),
+    ) {
+        let Some(root) = &mut self.root else {
+            self.root = Some(Node::Leaf(id));
+            return;
+        };
+
+        let target = focused
+            .filter(|focused| root.contains(*focused))
+            .or_else(|| {
+                let mut leaves = Vec::new();
+                root.leaves(&mut leaves);
+                leaves.last().copied()
+            });
+        let Some(target) = target else {
+            return;
+        };
+
+        let (split, ratio, new_first) = match self.preselection.take() {
+            Some(preselection) => (
+                preselection.direction.split(),
+                preselection.ratio,
+                preselection.direction.is_first(),
+            ),
+            None => {
+                let rects = self.rects(area, gaps);
+                let rect = rects
+                    .iter()
+                    .find(|(leaf, _)| *leaf == target)
+                    .map_or(area, |(_, rect)| *rect);
+                let split = if rect.width >= rect.height {
+                    Split::Columns
+                } else {
+                    Split::Rows
+                };
+                (split, 0.5, false)
+            }
+        };
+
+        let Some(node) = self.root.as_mut().and_then(|r| r.leaf_mut(target))
+        else {
+            return;
+        };
+
+        let (first, second) = if new_first {
+            (Node::Leaf(id), Node::Leaf(target))
+        } else {
+            (Node::Leaf(target), Node::Leaf(id))
+        };
+
+        *node = Node::Split {
+            split,
+            ratio: ratio.clamp(MIN_RATIO, MAX_RATIO),
+            first: Box::new(first),
+            second: Box::new(second),
+        };
+    }
+
+    /// Removes the leaf `id`, its sibling takes over the space of the split
+    pub fn remove(&mut self, id: u64) {
+        let Some(root) = &mut self.root else {
+            return;
+        };
+
+        if matches!(root, Node::Leaf(leaf) if *leaf == id) {
+            self.root = None;
+            return;
+        }
+
+        if let Some(parent) = root.parent_mut(id) {
+            let Node::Split { first, second, .. } =
+                std::mem::replace(parent, Node::Leaf(0))
+            else {
+                unreachable!();
+            };
+
+            let keep = if matches!(*first, Node::Leaf(leaf) if leaf == id) {
+                second
+            } else {
+                first
+            };
+            *parent = *keep;
+        }
+    }
+
+    /// Makes the tree contain exactly `ids`, new clients are inserted next
+    /// to the first of `focus_order` that is already in the tree
+    pub fn sync(
+        &mut self,
+        ids: &[u64],
+        focus_order: &[u64],
+        area: Rect,
+        gaps: (i32, i32),
+    ) {
+        for leaf in self.leaves() {
+            if !ids.contains(&leaf) {
+                self.remove(leaf);
+            }
+        }
+
+        for &id in ids {
+            if !self.contains(id) {
+                let focused = focus_order
+                    .iter()
+                    .copied()
+                    .find(|focused| self.contains(*focused));
+                self.insert(id, focused, area, gaps);
+            }
+        }
+    }
+
+    /// Turns the split containing `id` by 90 degrees
+    pub fn rotate(&mut self, id: u64) {
+        if let Some(Node::Split { split, .. }) =
+            self.root.as_mut().and_then(|root| root.parent_mut(id))
+        {
+            *split = match split {
+                Split::Columns => Split::Rows,
+                Split::Rows => Split::Columns,
+            };
+        }
+    }
+
+    /// Swaps the two sides of the split containing `id`
+    pub fn flip(&mut self, id: u64) {
+        if let Some(Node::Split {
+            ratio,
+            first,
+            second,
+            ..
+        }) = self.root.as_mut().and_then(|root| root.parent_mut(id))
+        {
+            std::mem::swap(first, second);
+            *ratio = 1.0 - *ratio;
+        }
+    }
+
+    /// Resets the splits so that every client gets the same amount of space
+    pub fn balance(&mut self) {
+        if let Some(root) = &mut self.root {
+            root.balance();
+        }
+    }
+
+    /// Grows the client `id` by `delta` of the split containing it, a
+    /// negative `delta` shrinks it
+    pub fn resize(&mut self, id: u64, delta: f32) {
+        if let Some(Node::Split { ratio, first, .. }) =
+            self.root.as_mut().and_then(|root| root.parent_mut(id))
+        {
+            let grow_first = matches!(**first, Node::Leaf(leaf) if leaf == id);
+            let delta = if grow_first { delta } else { -delta };
+            *ratio = (*ratio + delta).clamp(MIN_RATIO, MAX_RATIO);
+        }
+    }
+
+    /// Swaps the leaves of the clients `a` and `b`
+    pub fn swap(&mut self, a: u64, b: u64) {
+        if let Some(root) = &mut self.root {
+            root.swap_ids(a, b);
+        }
+    }
+}
+
+/// The client closest to `id` in `direction`, `rects` are where the clients
+/// are on screen
+pub fn neighbour(
+    rects: &[(u64, Rect)],
+    id: u64,
+    direction: Direction,
+) -> Option<u64> {
+    let &(_, from) = rects.iter().find(|(leaf, _)| *leaf == id)?;
+
+    rects
+        .iter()
+        .filter(|(leaf, _)| *leaf != id)
+        .filter_map(|(leaf, rect)| {
+            neighbour_distance(from, *rect, direction)
+                .map(|distance| (distance, *leaf))
+        })
+        .min()
+        .map(|(_, leaf)| leaf)
+}
+
+/// How far `to` is from `from` when going in `direction`, `None` when `to`
+/// is not in that direction or doesn't line up with `from`
+fn neighbour_distance(
+    from: Rect,
+    to: Rect,
+    direction: Direction,
+) -> Option<i32> {
+    let overlap_x = to.x < from.x + from.width && from.x < to.x + to.width;
+    let overlap_y = to.y < from.y + from.height && from.y < to.y + to.height;
+
+    match direction {
+        Direction::Left if overlap_y && to.x + to.width <= from.x => {
+            Some(from.x - (to.x + to.width))
+        }
+        Direction::Right if overlap_y && to.x >= from.x + from.width => {
+            Some(to.x - (from.x + from.width))
+        }
+        Direction::Up if overlap_x && to.y + to.height <= from.y => {
+            Some(from.y - (to.y + to.height))
+        }
+        Direction::Down if overlap_x && to.y >= from.y + from.height => {
+            Some(to.y - (from.y + from.height))
+        }
+        _ => None,
+    }
+}
+
+impl Layout for Tree {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        let area = params.gapped_area();
+        let gaps = (params.gaps.inner_h, params.gaps.inner_v);
+        let rects = self.rects(area, gaps);
+
+        clients
+            .iter()
+            .map(|client| {
+                let cell = rects
+                    .iter()
+                    .find(|(leaf, _)| *leaf == client.id)
+                    .map_or(area, |(_, rect)| *rect);
+                client.inner(cell)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 1000,
+        height: 800,
+    };
+
+    fn tree(ids: &[u64]) -> Tree {
+        let mut tree = Tree::default();
+        for &id in ids {
+            tree.insert(id, ids.first().copied(), AREA, (0, 0));
+        }
+        tree
+    }
+
+    #[test]
+    fn insert_splits_the_longest_side_of_the_focused_leaf() {
+        let tree = tree(&[1, 2, 3]);
+        assert_eq!(
+            tree.rects(AREA, (0, 0)),
+            [
+                (1, Rect::new(0, 0, 500, 400)),
+                (3, Rect::new(0, 400, 500, 400)),
+                (2, Rect::new(500, 0, 500, 800)),
+            ]
+        );
+    }
+
+    #[test]
+    fn preselection_puts_the_next_client_on_its_side() {
+        let mut tree = tree(&[1]);
+        tree.preselect(Some(Preselection {
+            direction: Direction::Up,
+            ratio: 0.25,
+        }));
+        tree.insert(2, Some(1), AREA, (0, 0));
+
+        assert!(tree.preselection().is_none());
+        assert_eq!(
+            tree.rects(AREA, (0, 0)),
+            [
+                (2, Rect::new(0, 0, 1000, 200)),
+                (1, Rect::new(0, 200, 1000, 600))
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_gives_the_space_to_the_sibling() {
+        let mut tree = tree(&[1, 2, 3]);
+        tree.remove(3);
+        assert_eq!(tree.leaves(), [1, 2]);
+        assert_eq!(
+            tree.rects(AREA, (0, 0))[0],
+            (1, Rect::new(0, 0, 500, 800))
+        );
+
+        tree.sync(&[2], &[], AREA, (0, 0));
+        assert_eq!(tree.rects(AREA, (0, 0)), [(2, AREA)]);
+    }
+
+    #[test]
+    fn neighbour_is_the_closest_client_in_the_direction() {
+        let rects = tree(&[1, 2, 3]).rects(AREA, (0, 0));
+        assert_eq!(neighbour(&rects, 3, Direction::Right), Some(2));
+        assert_eq!(neighbour(&rects, 3, Direction::Up), Some(1));
+        assert_eq!(neighbour(&rects, 3, Direction::Left), None);
+    }
+
+    #[test]
+    fn directions_follow_the_transform() {
+        let transform = Transform {
+            mirror_h: true,
+            mirror_v: false,
+            rotate: true,
+        };
+        assert_eq!(Direction::Left.on_screen(transform), Direction::Up);
+        assert_eq!(Direction::Up.on_screen(transform), Direction::Right);
+        for direction in [
+            Direction::Left,
+            Direction::Right,
+            Direction::Up,
+            Direction::Down,
+        ] {
+            assert_eq!(
+                direction.in_tree(transform).on_screen(transform),
+                direction
+            );
+        }
+    }
+}
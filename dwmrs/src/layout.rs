@@ -0,0 +1,1827 @@
+//! Layout engine
+//!
+//! Layouts only deal with plain geometry, they get the work area of the
+//! monitor and a description of every tiled client and return where each
+//! client should go. Nothing in here touches X or the C side so the layouts
+//! can be used without a running X server.
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Shrinks the rectangle by `h` on the left and right side and by `v` on
+    /// the top and bottom
+    pub fn inset(&self, h: i32, v: i32) -> Rect {
+        Rect::new(
+            self.x + h,
+            self.y + v,
+            self.width - h * 2,
+            self.height - v * 2,
+        )
+    }
+
+    /// Splits the rectangle into a left part `width` pixels wide and a right
+    /// part with the rest, separated by `gap`
+    pub fn split_h(&self, width: i32, gap: i32) -> (Rect, Rect) {
+        let left = Rect::new(self.x, self.y, width, self.height);
+        let right = Rect::new(
+            self.x + width + gap,
+            self.y,
+            self.width - width - gap,
+            self.height,
+        );
+        (left, right)
+    }
+
+    /// Splits the rectangle into a top part `height` pixels high and a
+    /// bottom part with the rest, separated by `gap`
+    pub fn split_v(&self, height: i32, gap: i32) -> (Rect, Rect) {
+        let top = Rect::new(self.x, self.y, self.width, height);
+        let bottom = Rect::new(
+            self.x,
+            self.y + height + gap,
+            self.width,
+            self.height - height - gap,
+        );
+        (top, bottom)
+    }
+}
+
+/// Space left around windows, the horizontal gaps are measured along the x
+/// axis and the vertical gaps along the y axis
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct Gaps {
+    /// Between the windows and the left and right edge of the monitor
+    pub outer_h: i32,
+    /// Between the windows and the top and bottom edge of the monitor
+    pub outer_v: i32,
+    /// Between two windows next to each other
+    pub inner_h: i32,
+    /// Between two windows on top of each other
+    pub inner_v: i32,
+}
+
+/// The parts of a tiled client a layout cares about
+#[derive(Clone, Copy, Debug)]
+pub struct TiledClient {
+    /// Identifies the client for layouts that keep state between arranges
+    pub id: u64,
+    pub border_width: i32,
+    /// How much of its column the client gets compared to the other
+    /// clients in it
+    pub cfact: f32,
+}
+
+impl TiledClient {
+    /// Turns the outer rectangle of a client into the rectangle we should
+    /// pass to `resize()`, the border is drawn outside of the window so it
+    /// needs to be removed from the size
+    pub(crate) fn inner(&self, cell: Rect) -> Rect {
+        Rect::new(
+            cell.x,
+            cell.y,
+            cell.width - self.border_width * 2,
+            cell.height - self.border_width * 2,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LayoutParams {
+    /// The window area of the monitor
+    pub area: Rect,
+    pub mfact: f32,
+    pub nmaster: i32,
+    /// Place the master clients next to each other instead of on top of
+    /// each other
+    pub master_columns: bool,
+    pub gaps: Gaps,
+    /// Number of visible clients, floating clients included
+    pub visible: usize,
+}
+
+impl LayoutParams {
+    /// The window area with the outer gaps removed
+    pub fn gapped_area(&self) -> Rect {
+        self.area.inset(self.gaps.outer_h, self.gaps.outer_v)
+    }
+}
+
+/// Splits `length` pixels into parts sized by `weights` with `gap` pixels
+/// between them and returns the offset and size of every part. Any pixels
+/// left over from the division goes to the last parts.
+fn split(length: i32, weights: &[f32], gap: i32) -> Vec<(i32, i32)> {
+    let mut result = Vec::with_capacity(weights.len());
+
+    let available = length - gap * (weights.len() as i32 - 1);
+    let mut remaining: f32 = weights.iter().sum();
+    let mut used = 0;
+    for (index, weight) in weights.iter().enumerate() {
+        let size = if index + 1 == weights.len() {
+            available - used
+        } else {
+            ((available - used) as f32 * weight / remaining) as i32
+        };
+
+        result.push((used + gap * index as i32, size));
+        used += size;
+        remaining -= weight;
+    }
+
+    result
+}
+
+/// Splits `area` into rows on top of each other, see `split`
+fn rows(area: Rect, weights: &[f32], gap: i32) -> Vec<Rect> {
+    split(area.height, weights, gap)
+        .into_iter()
+        .map(|(offset, height)| {
+            Rect::new(area.x, area.y + offset, area.width, height)
+        })
+        .collect()
+}
+
+/// Splits `area` into columns next to each other, see `split`
+fn columns(area: Rect, weights: &[f32], gap: i32) -> Vec<Rect> {
+    split(area.width, weights, gap)
+        .into_iter()
+        .map(|(offset, width)| {
+            Rect::new(area.x + offset, area.y, width, area.height)
+        })
+        .collect()
+}
+
+/// Weights for `count` parts of the same size
+fn even(count: i32) -> Vec<f32> {
+    vec![1.0; count.max(0) as usize]
+}
+
+/// Weights from the size factors of the clients
+fn factors<'a>(
+    clients: impl IntoIterator<Item = &'a TiledClient>,
+) -> Vec<f32> {
+    clients.into_iter().map(|client| client.cfact).collect()
+}
+
+/// Splits the clients into the master clients and the stack clients
+fn split_master(
+    params: &LayoutParams,
+    clients: &[TiledClient],
+) -> (Vec<f32>, Vec<f32>) {
+    let nmaster = (params.nmaster.max(0) as usize).min(clients.len());
+    let (masters, stack) = clients.split_at(nmaster);
+    (factors(masters), factors(stack))
+}
+
+/// Splits the master area between the master clients, either in rows or
+/// in columns
+fn master_cells(
+    params: &LayoutParams,
+    area: Rect,
+    weights: &[f32],
+    in_columns: bool,
+) -> Vec<Rect> {
+    if in_columns {
+        columns(area, weights, params.gaps.inner_h)
+    } else {
+        rows(area, weights, params.gaps.inner_v)
+    }
+}
+
+/// Where the master area is for dragging the split between it and the
+/// stack, mfact is the share of the area between the edge the master area
+/// starts at and the split
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MasterSplit {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// Between two stack columns, mfact is its share of the width
+    CenterH,
+    /// Between two stack rows, mfact is its share of the height
+    CenterV,
+}
+
+impl MasterSplit {
+    /// Where the master area ends up once `transform` is applied
+    fn transformed(self, transform: Transform) -> Self {
+        let split = match self {
+            MasterSplit::Left if transform.rotate => MasterSplit::Top,
+            MasterSplit::Right if transform.rotate => MasterSplit::Bottom,
+            MasterSplit::Top if transform.rotate => MasterSplit::Left,
+            MasterSplit::Bottom if transform.rotate => MasterSplit::Right,
+            MasterSplit::CenterH if transform.rotate => MasterSplit::CenterV,
+            MasterSplit::CenterV if transform.rotate => MasterSplit::CenterH,
+            split => split,
+        };
+
+        match split {
+            MasterSplit::Left if transform.mirror_h => MasterSplit::Right,
+            MasterSplit::Right if transform.mirror_h => MasterSplit::Left,
+            MasterSplit::Top if transform.mirror_v => MasterSplit::Bottom,
+            MasterSplit::Bottom if transform.mirror_v => MasterSplit::Top,
+            split => split,
+        }
+    }
+}
+
+/// How the clients of the stack share it by their cfact
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackSplit {
+    Rows,
+    Columns,
+}
+
+/// Whether the area is split between the master and the stack, otherwise
+/// one of them gets everything
+fn has_master_split(params: &LayoutParams, count: usize) -> bool {
+    let nmaster = params.nmaster.max(0) as usize;
+    nmaster > 0 && count > nmaster
+}
+
+pub trait Layout {
+    /// Returns the target geometry for every client in `clients`, in the
+    /// same order
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect>;
+
+    /// Whether the clients should lose their border when smart borders are
+    /// enabled, by default only a client that is alone loses it
+    fn borderless(
+        &self,
+        _params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> bool {
+        clients.len() == 1
+    }
+
+    /// Layouts can override the symbol from the layout table, `None` keeps
+    /// the symbol as is
+    fn symbol(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<String> {
+        None
+    }
+
+    /// Where the master area is when mfact decides where it ends, `None`
+    /// when there is no split to drag
+    fn master_split(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        None
+    }
+
+    /// How the stack is shared by cfact, `None` when cfact is ignored
+    fn stack_split(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<StackSplit> {
+        None
+    }
+}
+
+pub struct Tile;
+
+/// Splits the area into the master column on the left and the stack column
+/// on the right, when one of them is empty the other one gets everything
+fn master_stack(params: &LayoutParams, count: i32) -> (Rect, Rect) {
+    let area = params.gapped_area();
+    let gaps = params.gaps;
+    let nmaster = params.nmaster.max(0);
+
+    if count > nmaster && nmaster > 0 {
+        let width = ((area.width - gaps.inner_h) as f32 * params.mfact) as i32;
+        area.split_h(width, gaps.inner_h)
+    } else {
+        (area, area)
+    }
+}
+
+/// Master column on the left and the stack on the right, `master_columns`
+/// decides how the master clients share the master column
+fn tile(
+    params: &LayoutParams,
+    clients: &[TiledClient],
+    master_columns: bool,
+) -> Vec<Rect> {
+    let gaps = params.gaps;
+    let (master, stack) = master_stack(params, clients.len() as i32);
+    let (masters, stacked) = split_master(params, clients);
+
+    let mut cells = master_cells(params, master, &masters, master_columns);
+    cells.extend(rows(stack, &stacked, gaps.inner_v));
+
+    clients
+        .iter()
+        .zip(cells)
+        .map(|(client, cell)| client.inner(cell))
+        .collect()
+}
+
+impl Layout for Tile {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        tile(params, clients, params.master_columns)
+    }
+
+    fn master_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        has_master_split(params, clients.len()).then_some(MasterSplit::Left)
+    }
+
+    fn stack_split(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<StackSplit> {
+        Some(StackSplit::Rows)
+    }
+}
+
+/// Like `Tile` but the master clients are always next to each other
+pub struct ColumnMaster;
+
+impl Layout for ColumnMaster {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        tile(params, clients, true)
+    }
+
+    fn master_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        has_master_split(params, clients.len()).then_some(MasterSplit::Left)
+    }
+
+    fn stack_split(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<StackSplit> {
+        Some(StackSplit::Rows)
+    }
+}
+
+/// Shows how many clients there are as `[n]`, keeps the symbol from the
+/// layout table when there are none
+fn count_symbol(count: usize) -> Option<String> {
+    (count > 0).then(|| format!("[{}]", count))
+}
+
+pub struct Monocle;
+
+impl Layout for Monocle {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        let area = params.gapped_area();
+        clients.iter().map(|client| client.inner(area)).collect()
+    }
+
+    fn borderless(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> bool {
+        true
+    }
+
+    fn symbol(
+        &self,
+        params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<String> {
+        count_symbol(params.visible)
+    }
+}
+
+/// Gives every client half of the space left by the previous one,
+/// alternating between splitting left/right and top/bottom. With `spiral`
+/// the new client rotates around the previous ones, otherwise the clients
+/// dwindle towards the bottom right corner.
+fn fibonacci(
+    params: &LayoutParams,
+    clients: &[TiledClient],
+    spiral: bool,
+) -> Vec<Rect> {
+    let gaps = params.gaps;
+
+    let mut result = Vec::with_capacity(clients.len());
+    let mut rest = params.gapped_area();
+    for (index, client) in clients.iter().enumerate() {
+        if index + 1 == clients.len() {
+            result.push(client.inner(rest));
+            break;
+        }
+
+        let min = client.border_width * 2;
+        let horizontal = index % 2 == 0;
+        let (size, gap) = if horizontal {
+            (rest.width, gaps.inner_h)
+        } else {
+            (rest.height, gaps.inner_v)
+        };
+
+        let first = if index == 0 {
+            ((size - gap) as f32 * params.mfact) as i32
+        } else {
+            (size - gap) / 2
+        };
+
+        if first <= min || size - first - gap <= min {
+            // Too small to split any further, the rest of the clients share
+            // what is left
+            result.push(client.inner(rest));
+            continue;
+        }
+
+        // The spiral puts the client on the right and bottom side every
+        // other turn so the remaining area moves around the screen
+        let flip = spiral && index % 4 >= 2;
+        let (cell, remaining) = match (horizontal, flip) {
+            (true, false) => rest.split_h(first, gap),
+            (false, false) => rest.split_v(first, gap),
+            (true, true) => {
+                let (left, right) = rest.split_h(size - first - gap, gap);
+                (right, left)
+            }
+            (false, true) => {
+                let (top, bottom) = rest.split_v(size - first - gap, gap);
+                (bottom, top)
+            }
+        };
+
+        result.push(client.inner(cell));
+        rest = remaining;
+    }
+
+    result
+}
+
+pub struct Spiral;
+
+impl Layout for Spiral {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        fibonacci(params, clients, true)
+    }
+
+    fn master_split(
+        &self,
+        _params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        // mfact is the share of the first client
+        (clients.len() > 1).then_some(MasterSplit::Left)
+    }
+}
+
+pub struct Dwindle;
+
+impl Layout for Dwindle {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        fibonacci(params, clients, false)
+    }
+
+    fn master_split(
+        &self,
+        _params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        (clients.len() > 1).then_some(MasterSplit::Left)
+    }
+}
+
+/// Splits the area into a left stack column, the master column and a right
+/// stack column. Columns without clients are empty, the first stack client
+/// goes to the right so with only one stack client we end up with the same
+/// thing as tile.
+fn three_columns(params: &LayoutParams, count: i32) -> (Rect, Rect, Rect) {
+    let area = params.gapped_area();
+    let gaps = params.gaps;
+    let nmaster = params.nmaster.max(0);
+    let stack_count = (count - nmaster).max(0);
+
+    let mut master = area;
+    let mut left = Rect::default();
+    let mut right = Rect::default();
+    if nmaster == 0 && stack_count == 1 {
+        right = area;
+    } else if nmaster == 0 {
+        (left, right) =
+            area.split_h((area.width - gaps.inner_h) / 2, gaps.inner_h);
+    } else if stack_count == 1 {
+        let width = ((area.width - gaps.inner_h) as f32 * params.mfact) as i32;
+        (master, right) = area.split_h(width, gaps.inner_h);
+    } else if stack_count > 1 {
+        let width =
+            ((area.width - gaps.inner_h * 2) as f32 * params.mfact) as i32;
+        let side = (area.width - gaps.inner_h * 2 - width) / 2;
+
+        let rest;
+        (left, rest) = area.split_h(side, gaps.inner_h);
+        (master, right) = rest.split_h(width, gaps.inner_h);
+    }
+
+    (left, master, right)
+}
+
+/// How the stack is shared between the two columns next to the master area
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SideColumns {
+    /// Every other client goes to the right column, starting with it
+    Alternate,
+    /// The first half of the stack goes to the right column and the second
+    /// half to the left one
+    Halves,
+}
+
+/// Master area in the middle of the screen with the stack split between a
+/// column on each side of it
+pub struct CenteredMaster {
+    pub sides: SideColumns,
+}
+
+impl Layout for CenteredMaster {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        let area = params.gapped_area();
+        let gaps = params.gaps;
+        let (left, master, right) =
+            three_columns(params, clients.len() as i32);
+
+        let (masters, stacked) = split_master(params, clients);
+        let goes_right = |index: usize| match self.sides {
+            SideColumns::Alternate => index.is_multiple_of(2),
+            SideColumns::Halves => index < stacked.len().div_ceil(2),
+        };
+        let (rights, lefts): (Vec<_>, Vec<_>) = stacked
+            .iter()
+            .enumerate()
+            .partition(|(index, _)| goes_right(*index));
+        let weights = |side: Vec<(usize, &f32)>| {
+            side.into_iter()
+                .map(|(_, &weight)| weight)
+                .collect::<Vec<_>>()
+        };
+
+        let mut master_cells =
+            master_cells(params, master, &masters, params.master_columns)
+                .into_iter();
+        let mut lefts = rows(left, &weights(lefts), gaps.inner_v).into_iter();
+        let mut rights =
+            rows(right, &weights(rights), gaps.inner_v).into_iter();
+
+        clients
+            .iter()
+            .enumerate()
+            .map(|(index, client)| {
+                let cell = match index.checked_sub(masters.len()) {
+                    None => master_cells.next(),
+                    Some(index) if goes_right(index) => rights.next(),
+                    Some(_) => lefts.next(),
+                };
+
+                client.inner(cell.unwrap_or(area))
+            })
+            .collect()
+    }
+
+    fn master_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        if !has_master_split(params, clients.len()) {
+            return None;
+        }
+
+        // With a single stack client there is only the right column, see
+        // `three_columns`
+        match clients.len() - params.nmaster as usize {
+            1 => Some(MasterSplit::Left),
+            _ => Some(MasterSplit::CenterH),
+        }
+    }
+
+    fn stack_split(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<StackSplit> {
+        Some(StackSplit::Rows)
+    }
+}
+
+/// The master area floats in the middle of the screen on top of the stack
+/// which is tiled in columns over the whole screen
+pub struct CenteredFloatingMaster;
+
+impl Layout for CenteredFloatingMaster {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        let area = params.gapped_area();
+        let gaps = params.gaps;
+        let count = clients.len() as i32;
+        let nmaster = params.nmaster.max(0);
+
+        let mut master = area;
+        if count > nmaster && nmaster > 0 {
+            // mfact decides the size along the longest side of the screen,
+            // the other side leaves a bit of the stack visible
+            let (width, height) = if area.width > area.height {
+                (
+                    (area.width as f32 * params.mfact) as i32,
+                    (area.height as f32 * 0.9) as i32,
+                )
+            } else {
+                (
+                    (area.width as f32 * 0.9) as i32,
+                    (area.height as f32 * params.mfact) as i32,
+                )
+            };
+
+            master = Rect::new(
+                area.x + (area.width - width) / 2,
+                area.y + (area.height - height) / 2,
+                width,
+                height,
+            );
+        }
+
+        let (masters, stacked) = split_master(params, clients);
+        let mut cells = columns(master, &masters, gaps.inner_h);
+        cells.extend(columns(area, &stacked, gaps.inner_h));
+
+        clients
+            .iter()
+            .zip(cells)
+            .map(|(client, cell)| client.inner(cell))
+            .collect()
+    }
+}
+
+/// Splits the area into a master area on top and the stack below it, the
+/// master clients are placed next to each other
+fn bottom_stack(
+    params: &LayoutParams,
+    clients: &[TiledClient],
+    stack_rows: bool,
+) -> Vec<Rect> {
+    let area = params.gapped_area();
+    let gaps = params.gaps;
+    let count = clients.len() as i32;
+    let nmaster = params.nmaster.max(0);
+
+    let mut master = area;
+    let mut stack = area;
+    if count > nmaster && nmaster > 0 {
+        let height =
+            ((area.height - gaps.inner_v) as f32 * params.mfact) as i32;
+        (master, stack) = area.split_v(height, gaps.inner_v);
+    }
+
+    let (masters, stacked) = split_master(params, clients);
+    let mut cells = columns(master, &masters, gaps.inner_h);
+    if stack_rows {
+        cells.extend(rows(stack, &stacked, gaps.inner_v));
+    } else {
+        cells.extend(columns(stack, &stacked, gaps.inner_h));
+    }
+
+    clients
+        .iter()
+        .zip(cells)
+        .map(|(client, cell)| client.inner(cell))
+        .collect()
+}
+
+/// Master on top with the stack clients next to each other below it
+pub struct BottomStack;
+
+impl Layout for BottomStack {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        bottom_stack(params, clients, false)
+    }
+
+    fn master_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        has_master_split(params, clients.len()).then_some(MasterSplit::Top)
+    }
+
+    fn stack_split(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<StackSplit> {
+        Some(StackSplit::Columns)
+    }
+}
+
+/// Master on top with the stack clients on top of each other below it
+pub struct BottomStackHoriz;
+
+impl Layout for BottomStackHoriz {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        bottom_stack(params, clients, true)
+    }
+
+    fn master_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        has_master_split(params, clients.len()).then_some(MasterSplit::Top)
+    }
+
+    fn stack_split(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<StackSplit> {
+        Some(StackSplit::Rows)
+    }
+}
+
+/// Splits `area` row by row into a grid of `count` cells that is as close
+/// to a square as possible. With `stretch` the cells on the last row share
+/// the whole width instead of leaving empty cells.
+fn grid_cells(area: Rect, count: i32, gaps: Gaps, stretch: bool) -> Vec<Rect> {
+    let mut cols = 0;
+    while cols * cols < count {
+        cols += 1;
+    }
+
+    let mut cells = Vec::with_capacity(count.max(0) as usize);
+    if cols > 0 {
+        let row_count = (count + cols - 1) / cols;
+        for (index, row) in rows(area, &even(row_count), gaps.inner_v)
+            .into_iter()
+            .enumerate()
+        {
+            let left = count - index as i32 * cols;
+            let in_row = left.min(cols);
+            let split = if stretch { in_row } else { cols };
+            cells.extend(
+                columns(row, &even(split), gaps.inner_h)
+                    .into_iter()
+                    .take(in_row as usize),
+            );
+        }
+    }
+
+    cells
+}
+
+fn grid(
+    params: &LayoutParams,
+    clients: &[TiledClient],
+    stretch: bool,
+) -> Vec<Rect> {
+    let area = params.gapped_area();
+    let cells = grid_cells(area, clients.len() as i32, params.gaps, stretch);
+
+    clients
+        .iter()
+        .zip(cells)
+        .map(|(client, cell)| client.inner(cell))
+        .collect()
+}
+
+pub struct Grid;
+
+impl Layout for Grid {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        grid(params, clients, false)
+    }
+
+    fn symbol(
+        &self,
+        _params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<String> {
+        count_symbol(clients.len())
+    }
+}
+
+pub struct GaplessGrid;
+
+impl Layout for GaplessGrid {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        grid(params, clients, true)
+    }
+
+    fn symbol(
+        &self,
+        _params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<String> {
+        count_symbol(clients.len())
+    }
+}
+
+/// Master area like tile but the stack clients are all placed on top of
+/// each other in the stack column like monocle
+pub struct Deck;
+
+impl Layout for Deck {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        let (master, stack) = master_stack(params, clients.len() as i32);
+        let (masters, _) = split_master(params, clients);
+
+        let mut masters =
+            rows(master, &masters, params.gaps.inner_v).into_iter();
+
+        clients
+            .iter()
+            .map(|client| client.inner(masters.next().unwrap_or(stack)))
+            .collect()
+    }
+
+    fn master_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        has_master_split(params, clients.len()).then_some(MasterSplit::Left)
+    }
+
+    fn symbol(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<String> {
+        // Show how many stack clients are hidden behind the top one
+        let stack_count = clients.len() as i32 - params.nmaster.max(0);
+        if stack_count > 0 {
+            Some(format!("[D {}]", stack_count - 1))
+        } else {
+            None
+        }
+    }
+}
+
+/// Monocle with a tab bar showing every tiled client
+pub struct Tabbed {
+    /// Height of the tab bar
+    pub height: i32,
+    /// Place the tab bar above the clients instead of below them
+    pub top: bool,
+    /// Hide the tab bar when there is only one client
+    pub auto_hide: bool,
+}
+
+impl Tabbed {
+    /// Where the tab bar goes, `None` when the tab bar is hidden
+    pub fn tab_bar(
+        &self,
+        params: &LayoutParams,
+        count: usize,
+    ) -> Option<Rect> {
+        if count == 0 || (self.auto_hide && count == 1) {
+            return None;
+        }
+
+        let area = params.gapped_area();
+        let y = if self.top {
+            area.y
+        } else {
+            area.y + area.height - self.height
+        };
+
+        Some(Rect::new(area.x, y, area.width, self.height))
+    }
+
+    /// Splits the tab bar into one tab for each of the `count` clients, the
+    /// tabs are relative to the tab bar
+    pub fn tabs(&self, bar: Rect, count: usize) -> Vec<Rect> {
+        let bar = Rect::new(0, 0, bar.width, bar.height);
+        columns(bar, &even(count as i32), 0)
+    }
+}
+
+impl Layout for Tabbed {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        let mut area = params.gapped_area();
+        if self.tab_bar(params, clients.len()).is_some() {
+            let height = self.height + params.gaps.inner_v;
+            if self.top {
+                area.y += height;
+            }
+            area.height -= height;
+        }
+
+        clients.iter().map(|client| client.inner(area)).collect()
+    }
+}
+
+/// Mirrors and rotates what another layout does, this way every layout
+/// gets these variants without having to know about them
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct Transform {
+    /// Swap left and right, the master area ends up on the right
+    pub mirror_h: bool,
+    /// Swap top and bottom
+    pub mirror_v: bool,
+    /// Turn the layout by 90 degrees, rows become columns
+    pub rotate: bool,
+}
+
+impl Transform {
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::default()
+    }
+
+    /// The parameters the layout gets, a rotated layout works on an area
+    /// with the axes swapped
+    pub fn params(&self, params: &LayoutParams) -> LayoutParams {
+        if !self.rotate {
+            return *params;
+        }
+
+        let (area, gaps) = (params.area, params.gaps);
+        LayoutParams {
+            area: Rect::new(area.x, area.y, area.height, area.width),
+            gaps: Gaps {
+                outer_h: gaps.outer_v,
+                outer_v: gaps.outer_h,
+                inner_h: gaps.inner_v,
+                inner_v: gaps.inner_h,
+            },
+            ..*params
+        }
+    }
+
+    /// Applies the transform to `rect` which was placed inside `area`
+    pub fn apply(&self, area: Rect, rect: Rect) -> Rect {
+        let mut rect = rect;
+        if self.rotate {
+            // Swap the axes inside a transposed area, see `arrange`
+            rect = Rect::new(
+                area.x + (rect.y - area.y),
+                area.y + (rect.x - area.x),
+                rect.height,
+                rect.width,
+            );
+        }
+
+        if self.mirror_h {
+            rect.x = area.x + area.width - (rect.x - area.x) - rect.width;
+        }
+
+        if self.mirror_v {
+            rect.y = area.y + area.height - (rect.y - area.y) - rect.height;
+        }
+
+        rect
+    }
+
+    /// Changes the layout symbol so it shows the active transform, mirrored
+    /// layouts get the symbol reversed
+    pub fn symbol(&self, symbol: &str) -> String {
+        let mut result = if self.mirror_h {
+            symbol
+                .chars()
+                .rev()
+                .map(|c| match c {
+                    '[' => ']',
+                    ']' => '[',
+                    '(' => ')',
+                    ')' => '(',
+                    '<' => '>',
+                    '>' => '<',
+                    c => c,
+                })
+                .collect()
+        } else {
+            symbol.to_string()
+        };
+
+        if self.mirror_v {
+            result.push('v');
+        }
+
+        if self.rotate {
+            result.push('r');
+        }
+
+        result
+    }
+}
+
+/// `layout` with `transform` applied to it
+pub struct Transformed<'a> {
+    pub layout: &'a dyn Layout,
+    pub transform: Transform,
+}
+
+impl Layout for Transformed<'_> {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        if self.transform.is_identity() {
+            return self.layout.arrange(params, clients);
+        }
+
+        let area = params.area;
+        self.layout
+            .arrange(&self.transform.params(params), clients)
+            .into_iter()
+            .zip(clients)
+            .map(|(rect, client)| {
+                // Transform the outer rectangle, the border of a client
+                // does not move with the transform
+                let border = client.border_width * 2;
+                let outer = Rect::new(
+                    rect.x,
+                    rect.y,
+                    rect.width + border,
+                    rect.height + border,
+                );
+                let cell = self.transform.apply(area, outer);
+                client.inner(cell)
+            })
+            .collect()
+    }
+
+    fn symbol(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<String> {
+        self.layout.symbol(params, clients)
+    }
+
+    fn borderless(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> bool {
+        self.layout.borderless(params, clients)
+    }
+
+    fn master_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        self.layout
+            .master_split(&self.transform.params(params), clients)
+            .map(|split| split.transformed(self.transform))
+    }
+
+    fn stack_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<StackSplit> {
+        let split = self
+            .layout
+            .stack_split(&self.transform.params(params), clients)?;
+        Some(match split {
+            StackSplit::Rows if self.transform.rotate => StackSplit::Columns,
+            StackSplit::Columns if self.transform.rotate => StackSplit::Rows,
+            split => split,
+        })
+    }
+}
+
+/// Where the master area of `FlexTile` goes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlexPosition {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// In the middle with the stack areas on both sides
+    Center,
+}
+
+/// How the clients of a `FlexTile` area share it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlexSplit {
+    Rows,
+    Columns,
+    Monocle,
+    Grid,
+}
+
+impl FlexSplit {
+    fn parse(c: char) -> Result<Self, String> {
+        match c {
+            'r' => Ok(FlexSplit::Rows),
+            'c' => Ok(FlexSplit::Columns),
+            'm' => Ok(FlexSplit::Monocle),
+            'g' => Ok(FlexSplit::Grid),
+            c => Err(format!("unknown split '{}'", c)),
+        }
+    }
+
+    /// Splits `area` into cells sized by `weights`
+    fn cells(self, area: Rect, weights: &[f32], gaps: Gaps) -> Vec<Rect> {
+        match self {
+            FlexSplit::Rows => rows(area, weights, gaps.inner_v),
+            FlexSplit::Columns => columns(area, weights, gaps.inner_h),
+            FlexSplit::Monocle => vec![area; weights.len()],
+            FlexSplit::Grid => {
+                grid_cells(area, weights.len() as i32, gaps, true)
+            }
+        }
+    }
+}
+
+/// Tile layout described by a layout string of four characters:
+///
+/// 1. where the master area goes, `l`eft, `r`ight, `t`op, `b`ottom or
+///    `c`enter
+/// 2. how the master clients share the master area
+/// 3. how the stack clients share a stack area
+/// 4. how many stack areas there are, `1` to `9`
+///
+/// Areas are shared in `r`ows, `c`olumns, `m`onocle or a `g`rid. `"lrr1"`
+/// is the same as tile and `"tcc1"` the same as bottom stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlexTile {
+    pub position: FlexPosition,
+    pub master: FlexSplit,
+    pub stack: FlexSplit,
+    pub stack_areas: usize,
+}
+
+impl FlexTile {
+    pub fn parse(layout: &str) -> Result<Self, String> {
+        let chars = layout.chars().collect::<Vec<_>>();
+        let [position, master, stack, count] = chars[..] else {
+            return Err(format!(
+                "expected 4 characters but got {}",
+                chars.len()
+            ));
+        };
+
+        let position = match position {
+            'l' => FlexPosition::Left,
+            'r' => FlexPosition::Right,
+            't' => FlexPosition::Top,
+            'b' => FlexPosition::Bottom,
+            'c' => FlexPosition::Center,
+            c => return Err(format!("unknown master position '{}'", c)),
+        };
+
+        let stack_areas = count
+            .to_digit(10)
+            .filter(|count| *count > 0)
+            .ok_or_else(|| {
+                format!("stack count '{}' is not a number from 1 to 9", count)
+            })?;
+
+        Ok(Self {
+            position,
+            master: FlexSplit::parse(master)?,
+            stack: FlexSplit::parse(stack)?,
+            stack_areas: stack_areas as usize,
+        })
+    }
+
+    /// Splits the area into the master area and `count` stack areas
+    fn areas(
+        &self,
+        params: &LayoutParams,
+        has_master: bool,
+        count: usize,
+    ) -> (Rect, Vec<Rect>) {
+        let area = params.gapped_area();
+        let gaps = params.gaps;
+        let weights = even(count as i32);
+
+        let side_by_side =
+            !matches!(self.position, FlexPosition::Top | FlexPosition::Bottom);
+        let stack_areas = |rest: Rect| {
+            if side_by_side {
+                columns(rest, &weights, gaps.inner_h)
+            } else {
+                rows(rest, &weights, gaps.inner_v)
+            }
+        };
+
+        if count == 0 {
+            return (area, Vec::new());
+        }
+
+        if !has_master {
+            return (area, stack_areas(area));
+        }
+
+        let width = ((area.width - gaps.inner_h) as f32 * params.mfact) as i32;
+        let height =
+            ((area.height - gaps.inner_v) as f32 * params.mfact) as i32;
+        match self.position {
+            FlexPosition::Left => {
+                let (master, rest) = area.split_h(width, gaps.inner_h);
+                (master, stack_areas(rest))
+            }
+            FlexPosition::Right => {
+                let (rest, master) = area
+                    .split_h(area.width - width - gaps.inner_h, gaps.inner_h);
+                (master, stack_areas(rest))
+            }
+            FlexPosition::Top => {
+                let (master, rest) = area.split_v(height, gaps.inner_v);
+                (master, stack_areas(rest))
+            }
+            FlexPosition::Bottom => {
+                let (rest, master) = area.split_v(
+                    area.height - height - gaps.inner_v,
+                    gaps.inner_v,
+                );
+                (master, stack_areas(rest))
+            }
+            FlexPosition::Center if count == 1 => {
+                let (master, rest) = area.split_h(width, gaps.inner_h);
+                (master, vec![rest])
+            }
+            FlexPosition::Center => {
+                // The first half of the stack areas goes to the right
+                let width = ((area.width - gaps.inner_h * 2) as f32 *
+                    params.mfact) as i32;
+                let side = (area.width - gaps.inner_h * 2 - width) / 2;
+                let (left, rest) = area.split_h(side, gaps.inner_h);
+                let (master, right) = rest.split_h(width, gaps.inner_h);
+
+                let rights = count.div_ceil(2);
+                let mut areas =
+                    columns(right, &even(rights as i32), gaps.inner_h);
+                areas.extend(columns(
+                    left,
+                    &even((count - rights) as i32),
+                    gaps.inner_h,
+                ));
+                (master, areas)
+            }
+        }
+    }
+}
+
+impl Layout for FlexTile {
+    fn arrange(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Vec<Rect> {
+        let gaps = params.gaps;
+        let (masters, stacked) = split_master(params, clients);
+
+        let count = self.stack_areas.min(stacked.len());
+        let (master, areas) = self.areas(params, !masters.is_empty(), count);
+
+        let mut cells = self.master.cells(master, &masters, gaps);
+
+        // Spread the stack clients over the stack areas, the first areas
+        // get the extra clients
+        let mut rest = &stacked[..];
+        for (index, area) in areas.iter().enumerate() {
+            let left = areas.len() - index;
+            let (part, remaining) = rest.split_at(rest.len().div_ceil(left));
+            cells.extend(self.stack.cells(*area, part, gaps));
+            rest = remaining;
+        }
+
+        clients
+            .iter()
+            .zip(cells)
+            .map(|(client, cell)| client.inner(cell))
+            .collect()
+    }
+
+    fn master_split(
+        &self,
+        params: &LayoutParams,
+        clients: &[TiledClient],
+    ) -> Option<MasterSplit> {
+        if !has_master_split(params, clients.len()) {
+            return None;
+        }
+
+        let stacked = clients.len() - params.nmaster.max(0) as usize;
+        Some(match self.position {
+            FlexPosition::Left => MasterSplit::Left,
+            FlexPosition::Right => MasterSplit::Right,
+            FlexPosition::Top => MasterSplit::Top,
+            FlexPosition::Bottom => MasterSplit::Bottom,
+            // A single stack area goes to the right, see `areas`
+            FlexPosition::Center if self.stack_areas.min(stacked) == 1 => {
+                MasterSplit::Left
+            }
+            FlexPosition::Center => MasterSplit::CenterH,
+        })
+    }
+
+    fn stack_split(
+        &self,
+        _params: &LayoutParams,
+        _clients: &[TiledClient],
+    ) -> Option<StackSplit> {
+        match self.stack {
+            FlexSplit::Rows => Some(StackSplit::Rows),
+            FlexSplit::Columns => Some(StackSplit::Columns),
+            FlexSplit::Monocle | FlexSplit::Grid => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(count: usize, gaps: Gaps) -> LayoutParams {
+        LayoutParams {
+            area: Rect::new(0, 0, 1000, 800),
+            mfact: 0.5,
+            nmaster: 1,
+            master_columns: false,
+            gaps,
+            visible: count,
+        }
+    }
+
+    fn clients(count: usize) -> Vec<TiledClient> {
+        (0..count)
+            .map(|id| TiledClient {
+                id: id as u64,
+                border_width: 1,
+                cfact: 1.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_gives_the_rest_to_the_last_part() {
+        assert_eq!(
+            split(100, &[1.0, 1.0, 1.0], 0),
+            [(0, 33), (33, 33), (66, 34)]
+        );
+        assert_eq!(split(110, &[1.0, 1.0], 10), [(0, 50), (60, 50)]);
+        assert_eq!(split(100, &[3.0, 1.0], 0), [(0, 75), (75, 25)]);
+        assert_eq!(split(100, &[2.0], 0), [(0, 100)]);
+    }
+
+    #[test]
+    fn tile_puts_the_master_left_of_the_stack() {
+        let cells = Tile.arrange(&params(3, Gaps::default()), &clients(3));
+        assert_eq!(
+            cells,
+            [
+                Rect::new(0, 0, 498, 798),
+                Rect::new(500, 0, 498, 398),
+                Rect::new(500, 400, 498, 398),
+            ]
+        );
+    }
+
+    #[test]
+    fn tile_keeps_the_gaps() {
+        let gaps = Gaps {
+            outer_h: 10,
+            outer_v: 10,
+            inner_h: 10,
+            inner_v: 10,
+        };
+        let cells = Tile.arrange(&params(3, gaps), &clients(3));
+        assert_eq!(
+            cells,
+            [
+                Rect::new(10, 10, 483, 778),
+                Rect::new(505, 10, 483, 383),
+                Rect::new(505, 405, 483, 383),
+            ]
+        );
+    }
+
+    #[test]
+    fn tile_gives_a_lone_client_everything() {
+        let cells = Tile.arrange(&params(1, Gaps::default()), &clients(1));
+        assert_eq!(cells, [Rect::new(0, 0, 998, 798)]);
+    }
+
+    #[test]
+    fn monocle_stacks_every_client_on_the_whole_area() {
+        let params = params(2, Gaps::default());
+        let clients = clients(2);
+        assert_eq!(
+            Monocle.arrange(&params, &clients),
+            [Rect::new(0, 0, 998, 798); 2]
+        );
+        assert!(Monocle.borderless(&params, &clients));
+        assert_eq!(Monocle.symbol(&params, &clients), Some("[2]".to_string()));
+    }
+
+    #[test]
+    fn spiral_turns_around_the_previous_clients() {
+        let cells = Spiral.arrange(&params(5, Gaps::default()), &clients(5));
+        assert_eq!(
+            cells,
+            [
+                Rect::new(0, 0, 498, 798),
+                Rect::new(500, 0, 498, 398),
+                Rect::new(750, 400, 248, 398),
+                Rect::new(500, 600, 248, 198),
+                Rect::new(500, 400, 248, 198),
+            ]
+        );
+    }
+
+    #[test]
+    fn dwindle_shrinks_towards_the_bottom_right() {
+        let cells = Dwindle.arrange(&params(5, Gaps::default()), &clients(5));
+        assert_eq!(
+            cells,
+            [
+                Rect::new(0, 0, 498, 798),
+                Rect::new(500, 0, 498, 398),
+                Rect::new(500, 400, 248, 398),
+                Rect::new(750, 400, 248, 198),
+                Rect::new(750, 600, 248, 198),
+            ]
+        );
+
+        // mfact only sizes the first split
+        let mut params = params(3, Gaps::default());
+        params.mfact = 0.7;
+        assert_eq!(
+            Dwindle.arrange(&params, &clients(3)),
+            [
+                Rect::new(0, 0, 698, 798),
+                Rect::new(700, 0, 298, 398),
+                Rect::new(700, 400, 298, 398),
+            ]
+        );
+    }
+
+    #[test]
+    fn fibonacci_stops_splitting_when_it_gets_too_small() {
+        let mut params = params(3, Gaps::default());
+        params.area = Rect::new(0, 0, 4, 4);
+        assert_eq!(
+            Spiral.arrange(&params, &clients(3)),
+            [Rect::new(0, 0, 2, 2); 3]
+        );
+
+        // Only the direction that is too small is left alone
+        params.area = Rect::new(0, 0, 4, 800);
+        assert_eq!(
+            Dwindle.arrange(&params, &clients(3)),
+            [
+                Rect::new(0, 0, 2, 798),
+                Rect::new(0, 0, 2, 398),
+                Rect::new(0, 400, 2, 398),
+            ]
+        );
+    }
+
+    #[test]
+    fn bottom_stacks_put_the_stack_below_the_master() {
+        let params = params(3, Gaps::default());
+        let clients = clients(3);
+        assert_eq!(
+            BottomStack.arrange(&params, &clients),
+            [
+                Rect::new(0, 0, 998, 398),
+                Rect::new(0, 400, 498, 398),
+                Rect::new(500, 400, 498, 398),
+            ]
+        );
+        assert_eq!(
+            BottomStackHoriz.arrange(&params, &clients),
+            [
+                Rect::new(0, 0, 998, 398),
+                Rect::new(0, 400, 998, 198),
+                Rect::new(0, 600, 998, 198),
+            ]
+        );
+    }
+
+    #[test]
+    fn grids_show_the_client_count_like_monocle() {
+        let params = params(3, Gaps::default());
+        let clients = clients(3);
+        assert_eq!(Grid.symbol(&params, &clients), Some("[3]".to_string()));
+        assert_eq!(
+            GaplessGrid.symbol(&params, &clients),
+            Some("[3]".to_string())
+        );
+        assert_eq!(Grid.symbol(&params, &[]), None);
+    }
+
+    #[test]
+    fn deck_stacks_the_stack_and_counts_the_hidden_clients() {
+        let params = params(4, Gaps::default());
+        let clients = clients(4);
+        assert_eq!(
+            Deck.arrange(&params, &clients),
+            [
+                Rect::new(0, 0, 498, 798),
+                Rect::new(500, 0, 498, 798),
+                Rect::new(500, 0, 498, 798),
+                Rect::new(500, 0, 498, 798),
+            ]
+        );
+        assert_eq!(Deck.symbol(&params, &clients), Some("[D 2]".to_string()));
+        assert_eq!(Deck.symbol(&params, &clients[..1]), None);
+    }
+
+    #[test]
+    fn tabbed_leaves_room_for_the_tab_bar() {
+        let gaps = Gaps {
+            outer_h: 10,
+            outer_v: 10,
+            inner_h: 10,
+            inner_v: 10,
+        };
+        let params = params(3, gaps);
+        let clients = clients(3);
+        let mut tabbed = Tabbed {
+            height: 20,
+            top: true,
+            auto_hide: true,
+        };
+
+        let bar = tabbed.tab_bar(&params, clients.len());
+        assert_eq!(bar, Some(Rect::new(10, 10, 980, 20)));
+        assert_eq!(
+            tabbed.arrange(&params, &clients),
+            [Rect::new(10, 40, 978, 748); 3]
+        );
+        assert_eq!(
+            tabbed.tabs(bar.unwrap(), clients.len()),
+            [
+                Rect::new(0, 0, 326, 20),
+                Rect::new(326, 0, 327, 20),
+                Rect::new(653, 0, 327, 20),
+            ]
+        );
+
+        tabbed.top = false;
+        assert_eq!(
+            tabbed.tab_bar(&params, clients.len()),
+            Some(Rect::new(10, 770, 980, 20))
+        );
+        assert_eq!(
+            tabbed.arrange(&params, &clients),
+            [Rect::new(10, 10, 978, 748); 3]
+        );
+
+        // A lone client hides the tab bar
+        assert_eq!(tabbed.tab_bar(&params, 1), None);
+        assert_eq!(
+            tabbed.arrange(&params, &clients[..1]),
+            [Rect::new(10, 10, 978, 778)]
+        );
+    }
+
+    #[test]
+    fn centered_master_shares_the_stack_between_the_sides() {
+        let params = params(5, Gaps::default());
+        let clients = clients(5);
+        let columns = |sides| {
+            CenteredMaster { sides }
+                .arrange(&params, &clients)
+                .iter()
+                .map(|rect| rect.x)
+                .collect::<Vec<_>>()
+        };
+
+        let (left, master, right) = (0, 250, 750);
+        assert_eq!(
+            columns(SideColumns::Alternate),
+            [master, right, left, right, left]
+        );
+        assert_eq!(
+            columns(SideColumns::Halves),
+            [master, right, right, left, left]
+        );
+    }
+
+    #[test]
+    fn centered_master_has_no_split_without_a_stack() {
+        let layout = CenteredMaster {
+            sides: SideColumns::Alternate,
+        };
+        let mut params = params(1, Gaps::default());
+        params.nmaster = 3;
+        assert_eq!(layout.master_split(&params, &clients(1)), None);
+        assert_eq!(layout.master_split(&params, &clients(3)), None);
+        assert_eq!(
+            layout.master_split(&params, &clients(4)),
+            Some(MasterSplit::Left)
+        );
+        assert_eq!(
+            layout.master_split(&params, &clients(5)),
+            Some(MasterSplit::CenterH)
+        );
+    }
+
+    #[test]
+    fn flextile_parses_the_layout_string() {
+        assert_eq!(
+            FlexTile::parse("tcg2"),
+            Ok(FlexTile {
+                position: FlexPosition::Top,
+                master: FlexSplit::Columns,
+                stack: FlexSplit::Grid,
+                stack_areas: 2,
+            })
+        );
+        assert!(FlexTile::parse("lrr").is_err());
+        assert!(FlexTile::parse("xrr1").is_err());
+        assert!(FlexTile::parse("lxr1").is_err());
+        assert!(FlexTile::parse("lrr0").is_err());
+    }
+
+    #[test]
+    fn flextile_lrr1_is_tile() {
+        let flextile = FlexTile::parse("lrr1").unwrap();
+        for count in 1..5 {
+            let params = params(count, Gaps::default());
+            let clients = clients(count);
+            assert_eq!(
+                flextile.arrange(&params, &clients),
+                Tile.arrange(&params, &clients)
+            );
+        }
+    }
+
+    #[test]
+    fn transforms_move_the_cells_and_keep_borders_and_gaps() {
+        let mut params = params(3, Gaps::default());
+        params.area = Rect::new(100, 50, 1000, 800);
+        params.gaps = Gaps {
+            outer_h: 20,
+            outer_v: 10,
+            inner_h: 6,
+            inner_v: 4,
+        };
+        let mut clients = clients(3);
+        for client in &mut clients {
+            client.border_width = 2;
+        }
+        let arrange = |mirror_h, mirror_v, rotate| {
+            let transform = Transform {
+                mirror_h,
+                mirror_v,
+                rotate,
+            };
+            Transformed {
+                layout: &Tile,
+                transform,
+            }
+            .arrange(&params, &clients)
+        };
+
+        assert_eq!(
+            arrange(false, false, false),
+            [
+                Rect::new(120, 60, 473, 776),
+                Rect::new(603, 60, 473, 384),
+                Rect::new(603, 452, 473, 384),
+            ]
+        );
+        assert_eq!(
+            arrange(true, false, false),
+            [
+                Rect::new(603, 60, 473, 776),
+                Rect::new(120, 60, 473, 384),
+                Rect::new(120, 452, 473, 384),
+            ]
+        );
+        assert_eq!(
+            arrange(false, true, false),
+            [
+                Rect::new(120, 60, 473, 776),
+                Rect::new(603, 452, 473, 384),
+                Rect::new(603, 60, 473, 384),
+            ]
+        );
+        // The outer gaps stay on their edges of the screen
+        assert_eq!(
+            arrange(false, false, true),
+            [
+                Rect::new(120, 60, 956, 384),
+                Rect::new(120, 452, 473, 384),
+                Rect::new(603, 452, 473, 384),
+            ]
+        );
+    }
+
+    #[test]
+    fn master_split_follows_the_transform() {
+        let params = params(2, Gaps::default());
+        let clients = clients(2);
+        let split = |mirror_h, rotate| {
+            let transform = Transform {
+                mirror_h,
+                mirror_v: false,
+                rotate,
+            };
+            let transformed = Transformed {
+                layout: &Tile,
+                transform,
+            };
+            (
+                transformed.master_split(&params, &clients),
+                transformed.stack_split(&params, &clients),
+            )
+        };
+
+        assert_eq!(
+            split(false, false),
+            (Some(MasterSplit::Left), Some(StackSplit::Rows))
+        );
+        assert_eq!(
+            split(true, false),
+            (Some(MasterSplit::Right), Some(StackSplit::Rows))
+        );
+        assert_eq!(
+            split(false, true),
+            (Some(MasterSplit::Top), Some(StackSplit::Columns))
+        );
+        assert_eq!(Grid.master_split(&params, &clients), None);
+        assert_eq!(Tile.master_split(&params, &clients[..1]), None);
+    }
+}
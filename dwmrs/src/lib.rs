@@ -1,3 +1,9 @@
+#![allow(
+    clippy::missing_safety_doc,
+    reason = "the exported functions are only called by dwm.c, which passes \
+              valid pointers to its own structs"
+)]
+
 use x11::xlib::{
     Display, XSetErrorHandler, XErrorEvent, XSelectInput, XDefaultRootWindow,
     SubstructureRedirectMask, XSync, BadWindow, BadDrawable, BadMatch,
@@ -8,13 +14,28 @@ use x11::xlib::{
     Expose, FocusIn, KeyPress, MappingNotify, MapRequest, MotionNotify,
     PropertyNotify, ResizeRequest, UnmapNotify, XQueryTree,
     XGetWindowAttributes, XWindowAttributes, XGetTransientForHint, IsViewable,
-    XFree,
+    XFree, XSetWindowBorderWidth, XQueryPointer,
 };
 use x11::xft::{XftColor, XftFont, FcPattern};
-use std::ffi::{
-    c_int, c_uint, c_uchar, c_char, c_float, c_void, CString, CStr, c_long,
-};
-use std::time::Duration;
+use std::ffi::{c_int, c_uint, c_uchar, c_char, c_float, c_void, CStr, c_long};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use layout::{Gaps, LayoutParams, Rect, TiledClient};
+
+/// Reports a problem on stderr with the same prefix dwm.c uses
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        eprintln!("dwm: {}", format_args!($($arg)*))
+    };
+}
+
+pub mod bar;
+pub mod bsp;
+pub mod layout;
+pub mod placement;
+pub mod status;
 
 const X_CONFIGURE_WINDOW: c_uchar = 12;
 const X_GRAB_BUTTON: c_uchar = 28;
@@ -25,7 +46,11 @@ const X_POLY_SEGMENT: c_uchar = 66;
 const X_POLY_TEXT_8: c_uchar = 74;
 const X_POLY_FILL_RECTANGLE: c_uchar = 70;
 
-const BAR_ITEM_WIDTH: u32 = 40;
+const CLK_ROOT_WIN: c_int = 6;
+
+const LAYOUT_MIRROR_H: c_uint = 1 << 0;
+const LAYOUT_MIRROR_V: c_uint = 1 << 1;
+const LAYOUT_ROTATE: c_uint = 1 << 2;
 
 static mut DEFAULT_ERROR_HANDLER: Option<
     unsafe extern "C" fn(*mut Display, *mut XErrorEvent) -> c_int,
@@ -34,7 +59,6 @@ static mut DEFAULT_ERROR_HANDLER: Option<
 extern "C" {
     static scheme: *mut *mut XftColor;
     static bh: c_int;
-    static mut blw: c_int;
     static lrpad: c_int;
 
     static dpy: *mut Display;
@@ -44,8 +68,27 @@ extern "C" {
     static mons: *mut Monitor;
     static stext: [c_char; 256];
 
+    static statusbar: *const c_char;
+    static statuspidfile: *const c_char;
+    static showsystray: c_int;
+    static barwidgets: [Widget; 0];
+    static nbarwidgets: c_int;
+
     static mut running: c_int;
 
+    static gappih: c_uint;
+    static gappiv: c_uint;
+    static gappoh: c_uint;
+    static gappov: c_uint;
+    static smartgaps: c_int;
+
+    static smartborders: c_int;
+
+    static bspratio: c_float;
+
+    static toptab: c_int;
+    static tabautohide: c_int;
+
     static drw: *mut Drw;
 
     fn resize(
@@ -59,6 +102,13 @@ extern "C" {
 
     fn drw_setscheme(drw: *mut Drw, scheme: *mut XftColor);
 
+    fn drw_clr_create(
+        drw: *mut Drw,
+        dest: *mut XftColor,
+        name: *const c_char,
+        alpha: c_uint,
+    );
+
     fn drw_rect(
         drw: *mut Drw,
         x: c_int,
@@ -111,6 +161,10 @@ extern "C" {
 
     fn pop(client: *mut Client);
 
+    fn togglebar(arg: *const Arg);
+
+    fn restack(monitor: *mut Monitor);
+
     fn buttonpress(event: *mut XEvent);
     fn clientmessage(event: *mut XEvent);
     fn configurerequest(event: *mut XEvent);
@@ -127,6 +181,9 @@ extern "C" {
     fn getstate(w: Window) -> c_long;
 
     fn manage(w: Window, wa: *mut XWindowAttributes);
+
+    // Missing from the libc crate
+    fn sigqueue(pid: libc::pid_t, sig: c_int, value: libc::sigval) -> c_int;
 }
 
 static TAGS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
@@ -137,6 +194,7 @@ pub struct Client {
     name: [c_char; 256],
     min_aspect: c_float,
     max_aspect: c_float,
+    cfact: c_float,
 
     x: c_int,
     y: c_int,
@@ -159,6 +217,7 @@ pub struct Client {
 
     border_width: c_int,
     old_border_width: c_int,
+    default_border_width: c_int,
 
     tags: c_uint,
 
@@ -168,9 +227,15 @@ pub struct Client {
     never_focus: c_int,
     old_state: c_int,
     is_fullscreen: c_int,
+    is_sticky: c_int,
+    is_terminal: c_int,
+    no_swallow: c_int,
+    placement: c_int,
+    pid: c_int,
 
     next: *mut Client,
     stack_next: *mut Client,
+    swallowing: *mut Client,
     monitor: *mut Monitor,
 
     window: Window,
@@ -179,7 +244,8 @@ pub struct Client {
 impl Client {
     fn is_visable(&self) -> bool {
         let monitor = unsafe { &*self.monitor };
-        self.tags & monitor.tagset[monitor.seltags as usize] > 0
+        self.tags & monitor.tagset[monitor.seltags as usize] > 0 ||
+            self.is_sticky != 0
     }
 
     fn full_width(&self) -> i32 {
@@ -194,7 +260,23 @@ impl Client {
 #[repr(C)]
 pub struct Layout {
     symbol: *const c_char,
-    arrange: unsafe extern "C" fn(*mut Monitor),
+    arrange: Option<unsafe extern "C" fn(*mut Monitor)>,
+    flex_layout: *const c_char,
+}
+
+#[repr(C)]
+pub struct Rule {
+    class: *const c_char,
+    instance: *const c_char,
+    title: *const c_char,
+    tags: c_uint,
+    is_floating: c_int,
+    is_terminal: c_int,
+    no_swallow: c_int,
+    monitor: c_int,
+    border_width: c_int,
+    placement: c_int,
+    float_geometry: *const c_char,
 }
 
 #[derive(Debug)]
@@ -203,6 +285,8 @@ pub struct Monitor {
     ltsymbol: [c_uchar; 16],
     mfact: c_float,
     nmaster: c_int,
+    master_columns: c_int,
+    layout_modifiers: c_uint,
     num: c_int,
     by: c_int,
 
@@ -216,6 +300,12 @@ pub struct Monitor {
     ww: c_int,
     wh: c_int,
 
+    gap_inner_h: c_int,
+    gap_inner_v: c_int,
+    gap_outer_h: c_int,
+    gap_outer_v: c_int,
+    enable_gaps: c_int,
+
     seltags: c_uint,
     sellt: c_uint,
     tagset: [c_uint; 2],
@@ -226,10 +316,34 @@ pub struct Monitor {
     clients: *mut Client,
     sel: *mut Client,
     stack: *mut Client,
+    /// Points to `BspTrees`, kept opaque because the trees are not FFI safe
+    bsp: *mut c_void,
 
     next: *mut Monitor,
     bar_window: Window,
+    tab_window: Window,
+    tab_x: c_int,
+    tab_y: c_int,
+    tab_width: c_int,
+    master_split: c_int,
+    stack_split: c_int,
     lt: [*mut Layout; 2],
+    pertag: *mut Pertag,
+    monitor_rule: *const c_void,
+}
+
+/// Layout settings for every tag of a monitor, slot 0 is used when all the
+/// tags are viewed and slot `n` for tag `n`
+#[derive(Debug)]
+#[repr(C)]
+pub struct Pertag {
+    current_tag: c_uint,
+    previous_tag: c_uint,
+    nmasters: [c_int; TAGS.len() + 1],
+    mfacts: [c_float; TAGS.len() + 1],
+    sellts: [c_uint; TAGS.len() + 1],
+    lts: [[*mut Layout; 2]; TAGS.len() + 1],
+    show_bars: [c_int; TAGS.len() + 1],
 }
 
 #[repr(C)]
@@ -257,6 +371,13 @@ pub struct Drw {
     fonts: *mut Font,
 }
 
+/// A widget of the bar and the side it goes on
+#[repr(C)]
+pub struct Widget {
+    widget: c_int,
+    align: c_int,
+}
+
 #[repr(C)]
 pub union Arg {
     i: c_int,
@@ -336,111 +457,627 @@ unsafe fn next_tiled(mut client: *mut Client) -> *mut Client {
     return client;
 }
 
-#[no_mangle]
-pub unsafe extern "C" fn rust_monocle(monitor: *mut Monitor) {
-    let monitor = &mut *monitor;
+/// Writes `symbol` into the layout symbol of the monitor, truncating it if
+/// it doesn't fit
+fn set_layout_symbol(monitor: &mut Monitor, symbol: &str) {
+    let len = symbol.len().min(monitor.ltsymbol.len() - 1);
+    monitor.ltsymbol[..len].copy_from_slice(&symbol.as_bytes()[..len]);
+    monitor.ltsymbol[len] = 0;
+}
+
+/// Identifies a client for the layouts, the address of a client stays the
+/// same while it is managed, its window doesn't when it swallows another
+fn client_id(client: *const Client) -> u64 {
+    client as usize as u64
+}
+
+/// The tiled clients of the monitor in the order the layouts get them
+unsafe fn tiled_clients(monitor: &Monitor) -> Vec<*mut Client> {
+    let mut clients = Vec::new();
+    let mut client = next_tiled(monitor.clients);
+    while !client.is_null() {
+        clients.push(client);
+        client = next_tiled((*client).next);
+    }
+
+    clients
+}
 
-    let mut count = 0;
+/// Builds what the layouts need to know about the monitor, `tiled` is the
+/// number of tiled clients
+unsafe fn layout_params(monitor: &Monitor, tiled: usize) -> LayoutParams {
+    let mut visible = 0;
     let mut client = monitor.clients;
     while !client.is_null() {
         if (*client).is_visable() {
-            count += 1;
+            visible += 1;
         }
         client = (*client).next;
     }
 
-    if count > 0 {
-        // TODO(patrik): Find a better way to do this
-        let s = format!("[{}]", count);
-        let s = CString::new(s).unwrap();
+    let gaps = if monitor.enable_gaps == 0 || (smartgaps > 0 && tiled == 1) {
+        Gaps::default()
+    } else {
+        Gaps {
+            outer_h: monitor.gap_outer_h,
+            outer_v: monitor.gap_outer_v,
+            inner_h: monitor.gap_inner_h,
+            inner_v: monitor.gap_inner_v,
+        }
+    };
+
+    LayoutParams {
+        area: Rect::new(monitor.wx, monitor.wy, monitor.ww, monitor.wh),
+        mfact: monitor.mfact,
+        nmaster: monitor.nmaster,
+        master_columns: monitor.master_columns != 0,
+        gaps,
+        visible,
+    }
+}
 
-        let bytes = s.to_bytes_with_nul();
-        monitor.ltsymbol[..bytes.len()].clone_from_slice(bytes);
+/// The layout modifiers of the monitor
+fn layout_transform(monitor: &Monitor) -> layout::Transform {
+    let modifiers = monitor.layout_modifiers;
+    layout::Transform {
+        mirror_h: modifiers & LAYOUT_MIRROR_H != 0,
+        mirror_v: modifiers & LAYOUT_MIRROR_V != 0,
+        rotate: modifiers & LAYOUT_ROTATE != 0,
     }
+}
 
-    let mut client = next_tiled(monitor.clients);
-    while !client.is_null() {
-        let border_width = (*client).border_width;
-        resize(
-            client,
-            monitor.wx,
-            monitor.wy,
-            monitor.ww - border_width * 2,
-            monitor.wh - border_width * 2,
+/// The `Split*` value in dwm.c for `split`
+fn master_split_index(split: Option<layout::MasterSplit>) -> c_int {
+    match split {
+        None => 0,
+        Some(layout::MasterSplit::Left) => 1,
+        Some(layout::MasterSplit::Right) => 2,
+        Some(layout::MasterSplit::Top) => 3,
+        Some(layout::MasterSplit::Bottom) => 4,
+        Some(layout::MasterSplit::CenterH) => 5,
+        Some(layout::MasterSplit::CenterV) => 6,
+    }
+}
+
+/// The `Stack*` value in dwm.c for `split`
+fn stack_split_index(split: Option<layout::StackSplit>) -> c_int {
+    match split {
+        None => 0,
+        Some(layout::StackSplit::Rows) => 1,
+        Some(layout::StackSplit::Columns) => 2,
+    }
+}
+
+/// Runs `layout` on the tiled clients of the monitor, transformed by the
+/// layout modifiers of the monitor, and applies the result with `resize()`
+unsafe fn apply_layout(monitor: *mut Monitor, layout: &dyn layout::Layout) {
+    let transform = layout_transform(&*monitor);
+    apply_transformed_layout(monitor, layout, transform);
+}
+
+/// Like `apply_layout` but with a custom `transform`, the layout symbol
+/// still shows the layout modifiers of the monitor
+unsafe fn apply_transformed_layout(
+    monitor: *mut Monitor,
+    layout: &dyn layout::Layout,
+    transform: layout::Transform,
+) {
+    let monitor = &mut *monitor;
+
+    let clients = tiled_clients(monitor);
+    let mut tiled = clients
+        .iter()
+        .map(|&client| TiledClient {
+            id: client_id(client),
+            border_width: (*client).default_border_width,
+            cfact: (*client).cfact,
+        })
+        .collect::<Vec<_>>();
+
+    let params = layout_params(monitor, tiled.len());
+
+    let borderless = smartborders > 0 && layout.borderless(&params, &tiled);
+    for (&client, tiled) in clients.iter().zip(tiled.iter_mut()) {
+        if borderless {
+            tiled.border_width = 0;
+        }
+
+        // Size hints can keep `resize()` from touching the window so the
+        // border has to be updated here
+        if (*client).border_width != tiled.border_width {
+            (*client).border_width = tiled.border_width;
+            XSetWindowBorderWidth(
+                dpy,
+                (*client).window,
+                tiled.border_width as c_uint,
+            );
+        }
+    }
+
+    let symbol = layout.symbol(&params, &tiled).unwrap_or_else(|| {
+        CStr::from_ptr(monitor.ltsymbol.as_ptr() as *const c_char)
+            .to_string_lossy()
+            .into_owned()
+    });
+    set_layout_symbol(monitor, &layout_transform(monitor).symbol(&symbol));
+
+    let transformed = layout::Transformed { layout, transform };
+    let rects = layout::Layout::arrange(&transformed, &params, &tiled);
+
+    // Tells `dragsplit()` which splits follow the pointer
+    monitor.master_split = master_split_index(layout::Layout::master_split(
+        &transformed,
+        &params,
+        &tiled,
+    ));
+    monitor.stack_split = stack_split_index(layout::Layout::stack_split(
+        &transformed,
+        &params,
+        &tiled,
+    ));
+    for (&client, rect) in clients.iter().zip(rects) {
+        resize(client, rect.x, rect.y, rect.width, rect.height, 0);
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_monocle(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::Monocle);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_tile(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::Tile);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_column_master(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::ColumnMaster);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_three_column(monitor: *mut Monitor) {
+    let three_column = layout::CenteredMaster {
+        sides: layout::SideColumns::Halves,
+    };
+    apply_layout(monitor, &three_column);
+}
+
+/// Parses the flextile layout string of `layout`
+unsafe fn flextile(layout: &Layout) -> Result<layout::FlexTile, String> {
+    if layout.flex_layout.is_null() {
+        return Err("missing layout string".to_string());
+    }
+
+    let flex_layout = CStr::from_ptr(layout.flex_layout).to_string_lossy();
+    layout::FlexTile::parse(&flex_layout)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_flextile(monitor: *mut Monitor) {
+    let layout = &*(*monitor).lt[(*monitor).sellt as usize];
+
+    match flextile(layout) {
+        Ok(flextile) => apply_layout(monitor, &flextile),
+        Err(_) => apply_layout(monitor, &layout::Tile),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_spiral(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::Spiral);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_dwindle(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::Dwindle);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_centered_master(monitor: *mut Monitor) {
+    let centered_master = layout::CenteredMaster {
+        sides: layout::SideColumns::Alternate,
+    };
+    apply_layout(monitor, &centered_master);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_centered_floating_master(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::CenteredFloatingMaster);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_bstack(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::BottomStack);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_bstack_horiz(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::BottomStackHoriz);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_grid(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::Grid);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_gapless_grid(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::GaplessGrid);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_deck(monitor: *mut Monitor) {
+    apply_layout(monitor, &layout::Deck);
+}
+
+unsafe fn tabbed_layout() -> layout::Tabbed {
+    layout::Tabbed {
+        height: bh,
+        top: toptab > 0,
+        auto_hide: tabautohide > 0,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_tabbed(monitor: *mut Monitor) {
+    // The tab bar can't be turned on its side so the tabbed layout is never
+    // rotated, mirroring it only moves the tab bar to the other side
+    let transform = layout_transform(&*monitor);
+    let base = tabbed_layout();
+    let tabbed = layout::Tabbed {
+        top: base.top != transform.mirror_v,
+        ..base
+    };
+    apply_transformed_layout(monitor, &tabbed, layout::Transform::default());
+
+    let monitor = &mut *monitor;
+    let count = tiled_clients(monitor).len();
+    let params = layout_params(monitor, count);
+    if let Some(bar) = tabbed.tab_bar(&params, count) {
+        monitor.tab_x = bar.x;
+        monitor.tab_y = bar.y;
+        monitor.tab_width = bar.width;
+    }
+}
+
+/// Draws the tabs into the tab bar of the tabbed layout, `updatetabwin`
+/// moves and maps its window
+unsafe fn draw_tab_bar(monitor: &Monitor) {
+    if monitor.tab_window == 0 || monitor.tab_width <= 0 {
+        return;
+    }
+
+    let clients = tiled_clients(monitor);
+    let bar = Rect::new(monitor.tab_x, monitor.tab_y, monitor.tab_width, bh);
+    let tabs = tabbed_layout().tabs(bar, clients.len());
+    for (&client, tab) in clients.iter().zip(tabs) {
+        let selected = client == monitor.sel;
+        drw_setscheme(drw, *scheme.offset(selected as isize));
+        drw_text(
+            drw,
+            tab.x,
+            0,
+            tab.width as u32,
+            bh as u32,
+            (lrpad / 2) as u32,
+            (*client).name.as_ptr(),
             0,
         );
-        client = next_tiled((*client).next);
     }
+
+    drw_map(
+        drw,
+        monitor.tab_window,
+        0,
+        0,
+        monitor.tab_width as u32,
+        bh as u32,
+    );
 }
 
+/// Returns the index of the tab at `x` in the tab bar, -1 if there is no
+/// tab there
 #[no_mangle]
-pub unsafe extern "C" fn rust_tile(monitor: *mut Monitor) {
+pub unsafe extern "C" fn rust_tab_at(
+    monitor: *mut Monitor,
+    x: c_int,
+) -> c_int {
     let monitor = &*monitor;
 
-    let mut count = 0;
-    let mut client = monitor.clients;
+    let count = tiled_clients(monitor).len();
+    let bar = Rect::new(monitor.tab_x, monitor.tab_y, monitor.tab_width, bh);
+    tabbed_layout()
+        .tabs(bar, count)
+        .iter()
+        .position(|tab| x >= tab.x && x < tab.x + tab.width)
+        .map_or(-1, |index| index as c_int)
+}
+
+/// Focuses the client of the tab at index `arg.i`
+#[no_mangle]
+pub unsafe extern "C" fn rust_focus_tab(arg: *const Arg) {
+    let arg = &*arg;
+
+    let clients = tiled_clients(&*selmon);
+    if let Some(&client) = usize::try_from(arg.i)
+        .ok()
+        .and_then(|index| clients.get(index))
+    {
+        focus(client);
+        restack(selmon);
+    }
+}
+
+/// The split trees of the bsp layout on a monitor, one for every pertag
+/// slot
+pub struct BspTrees {
+    trees: [bsp::Tree; TAGS.len() + 1],
+}
+
+/// The split tree of the current tag on the monitor, the trees are created
+/// the first time they are needed
+unsafe fn bsp_tree(monitor: &mut Monitor) -> &mut bsp::Tree {
+    if monitor.bsp.is_null() {
+        let trees = BspTrees {
+            trees: std::array::from_fn(|_| bsp::Tree::default()),
+        };
+        monitor.bsp = Box::into_raw(Box::new(trees)) as *mut c_void;
+    }
+
+    let trees = &mut *(monitor.bsp as *mut BspTrees);
+    let slot = (*monitor.pertag).current_tag as usize;
+    &mut trees.trees[slot]
+}
+
+/// The area the bsp tree fills and the inner gaps between the splits, the
+/// tree is rotated along with the layout
+unsafe fn bsp_area(monitor: &Monitor) -> (Rect, (i32, i32)) {
+    let params = layout_params(monitor, tiled_clients(monitor).len());
+    let params = layout_transform(monitor).params(&params);
+    let gaps = (params.gaps.inner_h, params.gaps.inner_v);
+    (params.gapped_area(), gaps)
+}
+
+unsafe fn bsp_active(monitor: &Monitor) -> bool {
+    let layout = &*monitor.lt[monitor.sellt as usize];
+    let bsp: unsafe extern "C" fn(*mut Monitor) = rust_bsp;
+    layout
+        .arrange
+        .is_some_and(|arrange| std::ptr::fn_addr_eq(arrange, bsp))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_bsp(monitor: *mut Monitor) {
+    let m = &mut *monitor;
+
+    let ids = tiled_clients(m)
+        .iter()
+        .map(|&client| client_id(client))
+        .collect::<Vec<_>>();
+
+    let mut focus_order = Vec::new();
+    let mut client = m.stack;
     while !client.is_null() {
-        if (*client).is_visable() {
-            count += 1;
-        }
-        client = (*client).next;
+        focus_order.push(client_id(client));
+        client = (*client).stack_next;
+    }
+
+    let (area, gaps) = bsp_area(m);
+    let tree = bsp_tree(m);
+    tree.sync(&ids, &focus_order, area, gaps);
+    let preselection = tree.preselection();
+
+    apply_layout(monitor, tree);
+
+    // Show where the next client goes after the layout symbol
+    if let Some(preselection) = preselection {
+        let m = &mut *monitor;
+        let direction = preselection.direction.on_screen(layout_transform(m));
+        let symbol = CStr::from_ptr(m.ltsymbol.as_ptr() as *const c_char)
+            .to_string_lossy()
+            .into_owned();
+        set_layout_symbol(m, &format!("{} {}", symbol, direction.arrow()));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_bsp_free(monitor: *mut Monitor) {
+    let monitor = &mut *monitor;
+    if !monitor.bsp.is_null() {
+        drop(Box::from_raw(monitor.bsp as *mut BspTrees));
+        monitor.bsp = std::ptr::null_mut();
     }
+}
 
-    if count == 0 {
+/// Runs `f` on the bsp tree of the selected monitor with the selected
+/// client and rearranges the monitor afterwards, nothing happens when the
+/// monitor doesn't use the bsp layout
+unsafe fn with_bsp_tree<F>(f: F)
+where
+    F: FnOnce(&mut bsp::Tree, u64),
+{
+    let monitor = &mut *selmon;
+    if monitor.sel.is_null() || !bsp_active(monitor) {
         return;
     }
 
-    let mw = if count > monitor.nmaster {
-        if monitor.nmaster > 0 {
-            (monitor.ww as f32 * monitor.mfact) as i32
-        } else {
-            0
-        }
-    } else {
-        monitor.ww
+    let id = client_id(monitor.sel);
+    f(bsp_tree(monitor), id);
+
+    arrange(selmon);
+}
+
+/// Selects where the next client goes, selecting the same direction twice
+/// cancels the preselection. The direction is shown after the layout
+/// symbol.
+#[no_mangle]
+pub unsafe extern "C" fn rust_bsp_preselect(arg: *const Arg) {
+    let arg = &*arg;
+    let Some(direction) = bsp::Direction::from_index(arg.i) else {
+        return;
     };
 
-    let mut my = 0;
-    let mut ty = 0;
+    let monitor = &mut *selmon;
+    if !bsp_active(monitor) {
+        return;
+    }
 
-    let mut index = 0;
-    let mut client = next_tiled(monitor.clients);
-    while !client.is_null() {
-        let border_width = (*client).border_width;
-
-        if index < monitor.nmaster {
-            let h = (monitor.wh - my) / (count.min(monitor.nmaster) - index);
-            resize(
-                client,
-                monitor.wx,
-                monitor.wy + my,
-                mw - (border_width * 2),
-                h - (border_width * 2),
-                0,
-            );
+    let direction = direction.in_tree(layout_transform(monitor));
+    let tree = bsp_tree(monitor);
+    let preselection = match tree.preselection() {
+        Some(current) if current.direction == direction => None,
+        _ => Some(bsp::Preselection {
+            direction,
+            ratio: bspratio,
+        }),
+    };
+    tree.preselect(preselection);
 
-            if my + (*client).full_height() < monitor.wh {
-                my += (*client).full_height();
-            }
-        } else {
-            let h = (monitor.wh - ty) / (count - index);
-            resize(
-                client,
-                monitor.wx + mw,
-                monitor.wy + ty,
-                monitor.ww - mw - (border_width * 2),
-                h - (border_width * 2),
-                0,
-            );
+    arrange(selmon);
+}
 
-            if ty + (*client).full_height() < monitor.wh {
-                ty += (*client).full_height();
-            }
+#[no_mangle]
+pub unsafe extern "C" fn rust_bsp_rotate(_arg: *const Arg) {
+    with_bsp_tree(|tree, id| tree.rotate(id));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_bsp_flip(_arg: *const Arg) {
+    with_bsp_tree(|tree, id| tree.flip(id));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_bsp_balance(_arg: *const Arg) {
+    with_bsp_tree(|tree, _| tree.balance());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_bsp_resize(arg: *const Arg) {
+    let delta = (*arg).f;
+    with_bsp_tree(|tree, id| tree.resize(id, delta));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_bsp_swap(arg: *const Arg) {
+    let Some(direction) = bsp::Direction::from_index((*arg).i) else {
+        return;
+    };
+
+    // The clients are where the last arrange put them, so the neighbour is
+    // found on screen with the layout modifiers applied
+    let rects = tiled_clients(&*selmon)
+        .iter()
+        .map(|&client| (client_id(client), client_rect(&*client)))
+        .collect::<Vec<_>>();
+
+    with_bsp_tree(|tree, id| {
+        if let Some(other) = bsp::neighbour(&rects, id, direction) {
+            tree.swap(id, other);
         }
+    });
+}
 
-        client = next_tiled((*client).next);
-        index += 1;
+fn client_rect(client: &Client) -> Rect {
+    Rect::new(
+        client.x,
+        client.y,
+        client.full_width(),
+        client.full_height(),
+    )
+}
+
+unsafe fn pointer_position() -> Option<(i32, i32)> {
+    let (mut x, mut y, mut win_x, mut win_y) = (0, 0, 0, 0);
+    let (mut root_return, mut child) = (0, 0);
+    let mut mask = 0;
+
+    let found = XQueryPointer(
+        dpy,
+        root,
+        &mut root_return,
+        &mut child,
+        &mut x,
+        &mut y,
+        &mut win_x,
+        &mut win_y,
+        &mut mask,
+    );
+    (found != 0).then_some((x, y))
+}
+
+/// Moves a new floating client to where `placement` wants it, `parent` is
+/// the client of a transient and may be null
+#[no_mangle]
+pub unsafe extern "C" fn rust_place_client(
+    client: *mut Client,
+    parent: *const Client,
+    placement: c_int,
+) {
+    let Some(placement) = placement::Placement::from_index(placement) else {
+        warn!("unknown floating placement {}", placement);
+        return;
+    };
+
+    let c = &mut *client;
+    let monitor = &*c.monitor;
+
+    let mut floating = Vec::new();
+    let mut other = monitor.clients;
+    while !other.is_null() {
+        let o = &*other;
+        if other != client &&
+            o.is_visable() &&
+            o.is_floating != 0 &&
+            o.is_fullscreen == 0
+        {
+            floating.push(client_rect(o));
+        }
+        other = o.next;
     }
+
+    let around = placement::Surroundings {
+        area: Rect::new(monitor.wx, monitor.wy, monitor.ww, monitor.wh),
+        parent: parent.as_ref().map(client_rect),
+        pointer: if placement == placement::Placement::Pointer {
+            pointer_position()
+        } else {
+            None
+        },
+        floating: &floating,
+    };
+
+    let placed = placement::place(placement, client_rect(c), &around);
+    c.x = placed.x;
+    c.y = placed.y;
+}
+
+/// Resizes and moves a client to the floating `geometry` of its rule,
+/// returns 1 when the geometry also decided the position
+#[no_mangle]
+pub unsafe extern "C" fn rust_float_geometry(
+    client: *mut Client,
+    geometry: *const c_char,
+) -> c_int {
+    let geometry = CStr::from_ptr(geometry).to_string_lossy();
+    let Ok(geometry) = placement::Geometry::parse(&geometry) else {
+        return 0;
+    };
+
+    let c = &mut *client;
+    let monitor = &*c.monitor;
+    let area = Rect::new(monitor.wx, monitor.wy, monitor.ww, monitor.wh);
+    let border = c.default_border_width;
+
+    let window =
+        Rect::new(c.x, c.y, c.width + 2 * border, c.height + 2 * border);
+    let placed = geometry.apply(window, border, area);
+    c.x = placed.x;
+    c.y = placed.y;
+    c.width = placed.width - 2 * border;
+    c.height = placed.height - 2 * border;
+
+    geometry.position.is_some() as c_int
 }
 
 #[no_mangle]
@@ -462,217 +1099,250 @@ pub unsafe extern "C" fn rust_resize_bar_window(monitor: *mut Monitor) {
     );
 }
 
-#[no_mangle]
-pub unsafe extern "C" fn rust_draw_bar(monitor_ptr: *mut Monitor) {
-    let monitor = &*monitor_ptr;
-
-    const SHOW_SYS_TRAY: bool = false;
-
-    let systray_width =
-        if SHOW_SYS_TRAY && monitor_ptr == systraytomon(monitor_ptr) {
-            getsystraywidth()
-        } else {
-            0
-        };
+unsafe fn bar_widget_config() -> &'static [Widget] {
+    std::slice::from_raw_parts(
+        barwidgets.as_ptr(),
+        nbarwidgets.try_into().unwrap_or(0),
+    )
+}
 
-    let mut urg = 0;
-    let mut client = monitor.clients;
-    while !client.is_null() {
-        if (*client).is_urgent > 0 {
-            urg |= (*client).tags;
+/// Reports the parts of the config that can't be used, called once at
+/// startup. The code using them later just leaves them out or falls back to
+/// a default without complaining again.
+#[no_mangle]
+pub unsafe extern "C" fn rust_check_config(
+    layouts: *const Layout,
+    layout_count: c_int,
+    rules: *const Rule,
+    rule_count: c_int,
+) {
+    let flextile_arrange: unsafe extern "C" fn(*mut Monitor) = rust_flextile;
+    let layouts = std::slice::from_raw_parts(layouts, layout_count as usize);
+    for layout in layouts {
+        let is_flextile = layout.arrange.is_some_and(|arrange| {
+            std::ptr::fn_addr_eq(arrange, flextile_arrange)
+        });
+        if let (true, Err(error)) = (is_flextile, flextile(layout)) {
+            let symbol = CStr::from_ptr(layout.symbol).to_string_lossy();
+            warn!("flextile layout {}: {}, using tile", symbol, error);
         }
-
-        client = (*client).next;
     }
 
-    let tw = if monitor_ptr == selmon {
-        drw_setscheme(drw, *scheme.offset(0));
+    let rules = std::slice::from_raw_parts(rules, rule_count as usize);
+    for rule in rules.iter().filter(|rule| !rule.float_geometry.is_null()) {
+        let geometry = CStr::from_ptr(rule.float_geometry).to_string_lossy();
+        if let Err(error) = placement::Geometry::parse(&geometry) {
+            warn!("floating geometry {}: {}, ignoring it", geometry, error);
+        }
+    }
 
-        let tw = drw_fontset_getwidth(drw, stext.as_ptr()) + lrpad as u32;
-        let tw = tw as i32;
-        let tw = tw - lrpad / 2 + 2;
+    for config in bar_widget_config() {
+        if bar::widget(config.widget).is_none() ||
+            bar::Align::from_index(config.align).is_none()
+        {
+            warn!(
+                "unknown bar widget {} or alignment {}, leaving it out",
+                config.widget, config.align
+            );
+        }
+    }
+}
 
-        drw_text(
-            drw,
-            monitor.ww - tw - systray_width as i32,
-            0,
-            tw.try_into().unwrap(),
-            bh.try_into().unwrap(),
-            (lrpad / 2 - 2).try_into().unwrap(),
-            stext.as_ptr(),
-            0,
-        );
+/// The configured widgets of the bar on `monitor` and where they go
+unsafe fn bar_widgets(
+    bar: &bar::Bar,
+) -> Vec<(&'static dyn bar::BarWidget, (i32, i32))> {
+    let widgets = bar_widget_config()
+        .iter()
+        .filter_map(|config| {
+            Some((
+                bar::widget(config.widget)?,
+                bar::Align::from_index(config.align)?,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    let widths = widgets
+        .iter()
+        .map(|(widget, align)| (widget.measure(bar), *align))
+        .collect::<Vec<_>>();
+    let slots = bar::arrange(&widths, bar.monitor.ww);
+
+    widgets
+        .into_iter()
+        .map(|(widget, _)| widget)
+        .zip(slots)
+        .collect()
+}
 
-        tw
-    } else {
-        0
-    };
+#[no_mangle]
+pub unsafe extern "C" fn rust_draw_bar(monitor_ptr: *mut Monitor) {
+    let monitor = &*monitor_ptr;
 
     rust_resize_bar_window(monitor_ptr);
 
-    // TODO(patrik): Better way?
-    let arrow = CString::new("\u{e0b0}").unwrap();
-    let arrow_width =
-        drw_fontset_getwidth(drw, arrow.as_ptr() as *const c_char);
-
-    let mut x = 0;
-    for (index, tag) in TAGS.iter().enumerate() {
-        let tag = CString::new(*tag).unwrap();
-
-        let selected =
-            monitor.tagset[monitor.seltags as usize] & (1 << index) > 0;
-        let mut next_selected = false;
-        if (index + 1) < TAGS.len() {
-            next_selected = monitor.tagset[monitor.seltags as usize] &
-                (1 << (index + 1)) >
-                0;
+    let bar = bar::Bar {
+        monitor,
+        ptr: monitor_ptr,
+    };
+    for (widget, (x, width)) in bar_widgets(&bar) {
+        if width > 0 {
+            widget.draw(&bar, x, width);
         }
+    }
 
-        let selected_scheme = *scheme.offset(1);
-        let normal_scheme = *scheme.offset(0);
-
-        let mut normal_arrow_scheme = [
-            *normal_scheme.offset(1),
-            *normal_scheme.offset(1),
-            *normal_scheme.offset(2),
-        ];
+    drw_map(
+        drw,
+        monitor.bar_window,
+        0,
+        0,
+        monitor.ww.try_into().unwrap(),
+        bh.try_into().unwrap(),
+    );
 
-        let mut selected_arrow_scheme = [
-            *selected_scheme.offset(1),
-            *normal_scheme.offset(1),
-            *normal_scheme.offset(2),
-        ];
+    draw_tab_bar(monitor);
+}
 
-        if next_selected {
-            normal_arrow_scheme[0] = *normal_scheme.offset(1);
-            normal_arrow_scheme[1] = *selected_scheme.offset(1);
-        }
+/// Parses the status text again after `stext` changed
+#[no_mangle]
+pub unsafe extern "C" fn rust_update_status() {
+    bar::update_status();
+}
 
-        // Tag Text
-        drw_setscheme(
-            drw,
-            if selected {
-                selected_scheme
-            } else {
-                normal_scheme
-            },
-        );
+/// Returns the click region of the bar at `x` and sets `arg` for clicks on
+/// the tags and the status blocks
+#[no_mangle]
+pub unsafe extern "C" fn rust_bar_click(
+    monitor: *mut Monitor,
+    x: c_int,
+    arg: *mut Arg,
+) -> c_int {
+    let bar = bar::Bar {
+        monitor: &*monitor,
+        ptr: monitor,
+    };
 
-        let mut text_box_width = BAR_ITEM_WIDTH - arrow_width;
-        let text_padding;
-        if index == 0 {
-            text_box_width += 5;
-            text_padding = 13;
-        } else {
-            text_padding = 8;
+    let click = bar_widgets(&bar)
+        .into_iter()
+        .find(|(_, (start, width))| x >= *start && x < start + width)
+        .and_then(|(widget, (start, width))| {
+            widget.click(&bar, x - start, width)
+        });
+
+    match click {
+        Some(click) => {
+            if let Some(ui) = click.arg {
+                (*arg).ui = ui;
+            }
+            click.region
         }
+        None => CLK_ROOT_WIN,
+    }
+}
 
-        drw_text(
-            drw,
-            x,
-            0,
-            text_box_width,
-            bh.try_into().unwrap(),
-            text_padding,
-            tag.as_ptr(),
-            (urg & 1 << index) as i32,
-        );
+/// Whether process `pid` is called `name`, the kernel only keeps the first
+/// 15 bytes of the name
+fn process_is(pid: i32, name: &str) -> bool {
+    let name = &name.as_bytes()[..name.len().min(15)];
+    std::fs::read(format!("/proc/{}/comm", pid))
+        .is_ok_and(|comm| comm.strip_suffix(b"\n") == Some(name))
+}
 
-        // Arrow
-        drw_setscheme(
-            drw,
-            if selected {
-                selected_arrow_scheme.as_mut_ptr()
-            } else {
-                normal_arrow_scheme.as_mut_ptr()
-            },
-        );
+/// The pid of the status program found by name, 0 when it is not known
+static STATUS_PID: AtomicI32 = AtomicI32::new(0);
 
-        drw_text(
-            drw,
-            x + text_box_width as i32,
-            0,
-            arrow_width,
-            bh.try_into().unwrap(),
-            0,
-            arrow.as_ptr(),
-            0,
-        );
+/// When the last search for the status program by name found nothing
+static STATUS_SEARCH: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// How long a failed search for the status program is trusted, so clicks
+/// while it isn't running don't go through all of /proc every time
+const STATUS_SEARCH_INTERVAL: Duration = Duration::from_secs(5);
 
-        x += (arrow_width + text_box_width) as i32;
+/// The process that writes the status text. With `statuspidfile` its pid
+/// is read from there on every click, otherwise it is looked up by name and
+/// kept until the process goes away.
+unsafe fn status_pid() -> Option<i32> {
+    if !statuspidfile.is_null() {
+        let path = CStr::from_ptr(statuspidfile).to_string_lossy();
+        let pid = std::fs::read_to_string(&*path).ok()?.trim().parse().ok();
+        return pid.filter(|&pid| pid > 0);
     }
 
-    let w = drw_fontset_getwidth(
-        drw,
-        monitor.ltsymbol.as_ptr() as *const c_char,
-    ) + lrpad as u32;
+    let name = CStr::from_ptr(statusbar).to_string_lossy();
+    let pid = STATUS_PID.load(Ordering::Relaxed);
+    if pid > 0 && process_is(pid, &name) {
+        return Some(pid);
+    }
 
-    blw = w as i32;
+    let mut search = STATUS_SEARCH.lock().unwrap();
+    if search.is_some_and(|last| last.elapsed() < STATUS_SEARCH_INTERVAL) {
+        return None;
+    }
 
-    drw_setscheme(drw, *scheme.offset(0));
-    let s = CStr::from_ptr(monitor.ltsymbol.as_ptr() as *const c_char);
-    let x = drw_text(
-        drw,
-        x - 8,
-        0,
-        w,
-        bh.try_into().unwrap(),
-        (lrpad / 2).try_into().unwrap(),
-        monitor.ltsymbol.as_ptr() as *const c_char,
-        0,
-    );
+    let pid = std::fs::read_dir("/proc")
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .find(|&pid| process_is(pid, &name));
+    *search = pid.is_none().then(Instant::now);
+    STATUS_PID.store(pid.unwrap_or(0), Ordering::Relaxed);
+    pid
+}
 
-    let fonts = &*((*drw).fonts);
+/// Sends the status program the signal of a clicked status block, like
+/// dwmblocks expects it. `buttonpress` puts the block above the lowest byte
+/// of `arg.ui` and the value from the binding, the button, into it.
+#[no_mangle]
+pub unsafe extern "C" fn rust_signal_status(arg: *const Arg) {
+    let arg = (*arg).ui;
+    let block = (arg >> 8) as c_int;
+    let signal = libc::SIGRTMIN() + block;
+    if block == 0 || signal > libc::SIGRTMAX() {
+        return;
+    }
 
-    let boxs = fonts.height / 9;
-    let boxw = fonts.height / 6 + 2;
+    // sival_int shares the start of the union with sival_ptr
+    let mut value: libc::sigval = std::mem::zeroed();
+    *(std::ptr::addr_of_mut!(value) as *mut c_int) = (arg & 0xff) as c_int;
+
+    // The status program can have been restarted since its pid was looked
+    // up, so it is looked up once more when the signal can't be sent
+    for _ in 0..2 {
+        let Some(pid) = status_pid() else {
+            let name = CStr::from_ptr(statusbar).to_string_lossy();
+            warn!("no status program called {} is running", name);
+            return;
+        };
 
-    // TODO(patrik): Systray
-    let w = monitor.ww - tw /*- stw */ - x;
-
-    if w > bh {
-        if !monitor.sel.is_null() {
-            // TODO(patrik): Fix this
-            // drw_setscheme(drw, scheme[m == selmon ? SchemeSel : SchemeNorm]);
-
-            drw_setscheme(drw, *scheme.offset(0));
-            drw_text(
-                drw,
-                x,
-                0,
-                w.try_into().unwrap(),
-                bh.try_into().unwrap(),
-                (lrpad / 2).try_into().unwrap(),
-                (*monitor.sel).name.as_ptr(),
-                0,
-            );
-            if (*monitor.sel).is_floating > 0 {
-                drw_rect(
-                    drw,
-                    x + boxs as i32,
-                    boxs.try_into().unwrap(),
-                    boxw.try_into().unwrap(),
-                    boxw.try_into().unwrap(),
-                    (*monitor.sel).is_fixed,
-                    0,
-                );
-            }
-        } else {
-            drw_setscheme(drw, *scheme.offset(0));
-            drw_rect(drw, x, 0, w, bh, 1, 1);
+        if sigqueue(pid, signal, value) == 0 {
+            return;
         }
+        STATUS_PID.store(0, Ordering::Relaxed);
     }
 
-    drw_map(
-        drw,
-        monitor.bar_window,
-        0,
-        0,
-        monitor.ww.try_into().unwrap(),
-        bh.try_into().unwrap(),
+    warn!(
+        "can't signal the status program: {}",
+        std::io::Error::last_os_error()
     );
 }
 
+/// Where the systray goes in the bar of `monitor`, the right edge when
+/// there is no systray widget
+#[no_mangle]
+pub unsafe extern "C" fn rust_systray_x(monitor: *mut Monitor) -> c_int {
+    let bar = bar::Bar {
+        monitor: &*monitor,
+        ptr: monitor,
+    };
+
+    bar_widgets(&bar)
+        .into_iter()
+        .find(|(widget, _)| std::ptr::addr_eq(*widget, &bar::Systray))
+        .map_or_else(
+            || bar.monitor.ww - getsystraywidth() as i32,
+            |(_, (x, _))| x,
+        )
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn rust_draw_bars() {
     let mut monitor = mons;
@@ -816,6 +1486,35 @@ pub unsafe extern "C" fn rust_mapping_notify_event(event: *mut XEvent) {
     }
 }
 
+/// The pertag slot used when `tags` are viewed, the first of the tags
+/// decides unless all of them are viewed
+fn pertag_slot(tags: u32) -> u32 {
+    // Scratchpads live on tags above `TAGMASK` which don't have a slot
+    let tags = tags & TAGMASK;
+    if tags == TAGMASK {
+        0
+    } else {
+        tags.trailing_zeros() + 1
+    }
+}
+
+/// Swaps in the layout settings of the current pertag slot on the selected
+/// monitor
+unsafe fn load_pertag() {
+    let monitor = &mut *selmon;
+    let pertag = &*monitor.pertag;
+    let slot = pertag.current_tag as usize;
+
+    monitor.nmaster = pertag.nmasters[slot];
+    monitor.mfact = pertag.mfacts[slot];
+    monitor.sellt = pertag.sellts[slot];
+    monitor.lt = pertag.lts[slot];
+
+    if monitor.show_bar != pertag.show_bars[slot] {
+        togglebar(std::ptr::null());
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn rust_view(arg: *const Arg) {
     let arg = &*arg;
@@ -824,11 +1523,46 @@ pub unsafe extern "C" fn rust_view(arg: *const Arg) {
         return;
     }
 
+    let pertag = &mut *(*selmon).pertag;
+
     (*selmon).seltags ^= 1;
     if arg.ui & TAGMASK > 0 {
         (*selmon).tagset[(*selmon).seltags as usize] = arg.ui & TAGMASK;
+
+        pertag.previous_tag = pertag.current_tag;
+        pertag.current_tag = pertag_slot(arg.ui);
+    } else {
+        std::mem::swap(&mut pertag.previous_tag, &mut pertag.current_tag);
     }
 
+    load_pertag();
+
+    focus(std::ptr::null_mut());
+    arrange(selmon);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_toggle_view(arg: *const Arg) {
+    let arg = &*arg;
+    let monitor = &mut *selmon;
+
+    let tagset = monitor.tagset[monitor.seltags as usize] ^ (arg.ui & TAGMASK);
+    if tagset & TAGMASK == 0 {
+        return;
+    }
+
+    // Stay on the current slot as long as its tag is still viewed
+    let pertag = &mut *monitor.pertag;
+    let current = pertag.current_tag;
+    let slot = pertag_slot(tagset);
+    if slot == 0 || current == 0 || tagset & (1 << (current - 1)) == 0 {
+        pertag.previous_tag = current;
+        pertag.current_tag = slot;
+    }
+
+    monitor.tagset[monitor.seltags as usize] = tagset;
+    load_pertag();
+
     focus(std::ptr::null_mut());
     arrange(selmon);
 }
@@ -838,7 +1572,7 @@ pub unsafe extern "C" fn rust_zoom(_arg: *const Arg) {
     let mut client = (*selmon).sel;
 
     let sellt = (*selmon).sellt as usize;
-    let first = ((*(*selmon).lt[sellt]).arrange as *const c_void).is_null();
+    let first = (*(*selmon).lt[sellt]).arrange.is_none();
     if first || ((*selmon).sel.is_null() && (*(*selmon).sel).is_floating > 0) {
         return;
     }
@@ -855,6 +1589,113 @@ pub unsafe extern "C" fn rust_zoom(_arg: *const Arg) {
     pop(client);
 }
 
+/// Changes the size factor of the selected client by `arg.f`, zero resets
+/// the factor
+#[no_mangle]
+pub unsafe extern "C" fn rust_set_cfact(arg: *const Arg) {
+    let arg = &*arg;
+    let client = (*selmon).sel;
+
+    let sellt = (*selmon).sellt as usize;
+    let floating = (*(*selmon).lt[sellt]).arrange.is_none();
+    if client.is_null() || floating {
+        return;
+    }
+
+    let cfact = if arg.f == 0.0 {
+        1.0
+    } else {
+        (*client).cfact + arg.f
+    };
+
+    if !(0.25..=4.0).contains(&cfact) {
+        return;
+    }
+
+    (*client).cfact = cfact;
+    arrange(selmon);
+}
+
+/// Switches the master clients of the selected monitor between being on top
+/// of each other and next to each other
+#[no_mangle]
+pub unsafe extern "C" fn rust_toggle_master_columns(_arg: *const Arg) {
+    let monitor = &mut *selmon;
+    monitor.master_columns = (monitor.master_columns == 0) as c_int;
+    arrange(selmon);
+}
+
+/// Toggles the layout modifiers in `arg.ui` on the selected monitor
+#[no_mangle]
+pub unsafe extern "C" fn rust_toggle_layout_modifier(arg: *const Arg) {
+    let arg = &*arg;
+    (*selmon).layout_modifiers ^= arg.ui;
+    arrange(selmon);
+}
+
+/// Sets the gaps of the selected monitor, gaps can't go below zero
+unsafe fn set_gaps(outer_h: i32, outer_v: i32, inner_h: i32, inner_v: i32) {
+    let monitor = &mut *selmon;
+
+    monitor.gap_outer_h = outer_h.max(0);
+    monitor.gap_outer_v = outer_v.max(0);
+    monitor.gap_inner_h = inner_h.max(0);
+    monitor.gap_inner_v = inner_v.max(0);
+
+    arrange(selmon);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_inc_gaps(arg: *const Arg) {
+    let arg = &*arg;
+    let monitor = &*selmon;
+
+    set_gaps(
+        monitor.gap_outer_h + arg.i,
+        monitor.gap_outer_v + arg.i,
+        monitor.gap_inner_h + arg.i,
+        monitor.gap_inner_v + arg.i,
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_inc_outer_gaps(arg: *const Arg) {
+    let arg = &*arg;
+    let monitor = &*selmon;
+
+    set_gaps(
+        monitor.gap_outer_h + arg.i,
+        monitor.gap_outer_v + arg.i,
+        monitor.gap_inner_h,
+        monitor.gap_inner_v,
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_inc_inner_gaps(arg: *const Arg) {
+    let arg = &*arg;
+    let monitor = &*selmon;
+
+    set_gaps(
+        monitor.gap_outer_h,
+        monitor.gap_outer_v,
+        monitor.gap_inner_h + arg.i,
+        monitor.gap_inner_v + arg.i,
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_toggle_gaps(_arg: *const Arg) {
+    (*selmon).enable_gaps ^= 1;
+    arrange(selmon);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_default_gaps(_arg: *const Arg) {
+    (*selmon).enable_gaps = 1;
+    set_gaps(gappoh as i32, gappov as i32, gappih as i32, gappiv as i32);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn rust_run() {
     XSync(dpy, 0);
@@ -979,7 +1820,7 @@ pub unsafe extern "C" fn rust_send_to_monitor(
     rust_detach(client);
     rust_detach_stack(client);
     (*client).monitor = monitor;
-    (*client).tags = (*monitor).tagset[(*monitor).seltags as usize];
+    (*client).tags = (*monitor).tagset[(*monitor).seltags as usize] & TAGMASK;
     rust_attach(client);
     rust_attach_stack(client);
     focus(std::ptr::null_mut());
@@ -0,0 +1,373 @@
+//! Placement of new floating windows
+//!
+//! Decides where a floating window goes when it is managed. Like the layouts
+//! this only deals with geometry, the caller collects the work area, the
+//! pointer and the other floating windows.
+
+use crate::layout::Rect;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    /// Keep the position the window asked for
+    Requested,
+    /// Center on the work area of the monitor
+    Center,
+    /// Center on the parent of a transient, other windows center on the
+    /// monitor
+    Parent,
+    /// Center under the pointer
+    Pointer,
+    /// The free spot that overlaps the other floating windows the least
+    LeastOverlap,
+}
+
+impl Placement {
+    pub fn from_index(index: i32) -> Option<Self> {
+        match index {
+            0 => Some(Placement::Requested),
+            1 => Some(Placement::Center),
+            2 => Some(Placement::Parent),
+            3 => Some(Placement::Pointer),
+            4 => Some(Placement::LeastOverlap),
+            _ => None,
+        }
+    }
+}
+
+/// What a window can be placed against
+pub struct Surroundings<'a> {
+    /// The work area of the monitor, the window is kept inside of it
+    pub area: Rect,
+    pub parent: Option<Rect>,
+    pub pointer: Option<(i32, i32)>,
+    /// The other floating windows on the monitor
+    pub floating: &'a [Rect],
+}
+
+/// Moves `window` according to `placement`, the size is left alone
+pub fn place(
+    placement: Placement,
+    window: Rect,
+    around: &Surroundings,
+) -> Rect {
+    let area = around.area;
+    let placed = match placement {
+        Placement::Requested => window,
+        Placement::Center => center_on(window, area),
+        Placement::Parent => center_on(window, around.parent.unwrap_or(area)),
+        Placement::Pointer => match around.pointer {
+            Some((x, y)) => Rect::new(
+                x - window.width / 2,
+                y - window.height / 2,
+                window.width,
+                window.height,
+            ),
+            None => center_on(window, area),
+        },
+        Placement::LeastOverlap => {
+            least_overlap(window, area, around.floating)
+        }
+    };
+
+    clamp(placed, area)
+}
+
+fn center_on(window: Rect, on: Rect) -> Rect {
+    Rect::new(
+        on.x + (on.width - window.width) / 2,
+        on.y + (on.height - window.height) / 2,
+        window.width,
+        window.height,
+    )
+}
+
+/// Keeps as much of the window inside the area as possible, windows bigger
+/// than the area stick to its top left corner
+fn clamp(window: Rect, area: Rect) -> Rect {
+    let x = window.x.min(area.x + area.width - window.width).max(area.x);
+    let y = window
+        .y
+        .min(area.y + area.height - window.height)
+        .max(area.y);
+    Rect::new(x, y, window.width, window.height)
+}
+
+fn overlap(a: Rect, b: Rect) -> i64 {
+    let width = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+    let height = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+    width.max(0) as i64 * height.max(0) as i64
+}
+
+/// Tries the corners of the area and the spots right next to every other
+/// window, the first spot with the smallest overlap wins so windows fill
+/// the area from the top left
+fn least_overlap(window: Rect, area: Rect, floating: &[Rect]) -> Rect {
+    let (width, height) = (window.width, window.height);
+
+    let mut xs = vec![area.x, area.x + area.width - width];
+    let mut ys = vec![area.y, area.y + area.height - height];
+    for other in floating {
+        xs.push(other.x + other.width);
+        xs.push(other.x - width);
+        ys.push(other.y + other.height);
+        ys.push(other.y - height);
+    }
+    xs.sort_unstable();
+    ys.sort_unstable();
+
+    let mut best: Option<(i64, Rect)> = None;
+    for &y in &ys {
+        for &x in &xs {
+            let spot = clamp(Rect::new(x, y, width, height), area);
+            let cost =
+                floating.iter().map(|&other| overlap(spot, other)).sum();
+            if best.is_none_or(|(least, _)| cost < least) {
+                best = Some((cost, spot));
+            }
+        }
+    }
+
+    best.map_or(window, |(_, spot)| spot)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    Pixels(i32),
+    /// Share of the work area
+    Percent(i32),
+}
+
+impl Length {
+    fn resolve(self, total: i32) -> i32 {
+        match self {
+            Length::Pixels(pixels) => pixels,
+            Length::Percent(percent) => total * percent / 100,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Offset {
+    /// Counted from the right or bottom edge of the work area
+    pub from_end: bool,
+    pub length: Length,
+}
+
+/// Floating geometry in the style of X geometry strings, `WxH+X+Y` where
+/// every number can be followed by `%` to take a share of the work area. A
+/// `-` offset counts from the right or bottom edge, so `-0+0` is the top
+/// right corner. Either half can be left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Geometry {
+    pub size: Option<(Length, Length)>,
+    pub position: Option<(Offset, Offset)>,
+}
+
+impl Geometry {
+    pub fn parse(geometry: &str) -> Result<Self, String> {
+        if geometry.is_empty() {
+            return Err("empty geometry".to_string());
+        }
+
+        let mut rest = geometry;
+
+        let size = if rest.starts_with(['+', '-']) {
+            None
+        } else {
+            let width = parse_length(&mut rest)?;
+            rest = rest
+                .strip_prefix('x')
+                .ok_or_else(|| "expected 'x' after the width".to_string())?;
+            let height = parse_length(&mut rest)?;
+            if width == Length::Pixels(0) || height == Length::Pixels(0) {
+                return Err("the size can't be 0".to_string());
+            }
+            Some((width, height))
+        };
+
+        let position = if rest.is_empty() {
+            None
+        } else {
+            let x = parse_offset(&mut rest)?;
+            let y = parse_offset(&mut rest)?;
+            Some((x, y))
+        };
+
+        if !rest.is_empty() {
+            return Err(format!("unexpected '{}'", rest));
+        }
+
+        Ok(Self { size, position })
+    }
+
+    /// Resizes and moves `window` inside `area`, pixel sizes are the size of
+    /// the window without its `border`
+    pub fn apply(&self, window: Rect, border: i32, area: Rect) -> Rect {
+        let outer = |length: Length, total: i32| match length {
+            Length::Pixels(pixels) => pixels + 2 * border,
+            Length::Percent(_) => length.resolve(total),
+        };
+        let (width, height) = match self.size {
+            Some((width, height)) => {
+                (outer(width, area.width), outer(height, area.height))
+            }
+            None => (window.width, window.height),
+        };
+        let width = width.max(2 * border + 1);
+        let height = height.max(2 * border + 1);
+
+        let (x, y) = match self.position {
+            Some((x, y)) => {
+                let place = |offset: Offset, start: i32, total: i32, size| {
+                    let length = offset.length.resolve(total);
+                    if offset.from_end {
+                        start + total - size - length
+                    } else {
+                        start + length
+                    }
+                };
+                (
+                    place(x, area.x, area.width, width),
+                    place(y, area.y, area.height, height),
+                )
+            }
+            None => (window.x, window.y),
+        };
+
+        Rect::new(x, y, width, height)
+    }
+}
+
+fn parse_length(rest: &mut &str) -> Result<Length, String> {
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    if digits == 0 {
+        return Err(format!("expected a number at '{}'", rest));
+    }
+
+    let value = rest[..digits]
+        .parse::<i32>()
+        .map_err(|error| format!("'{}': {}", &rest[..digits], error))?;
+    *rest = &rest[digits..];
+
+    match rest.strip_prefix('%') {
+        Some(_) if value > 100 => {
+            Err(format!("{}% is more than the work area", value))
+        }
+        Some(after) => {
+            *rest = after;
+            Ok(Length::Percent(value))
+        }
+        None => Ok(Length::Pixels(value)),
+    }
+}
+
+fn parse_offset(rest: &mut &str) -> Result<Offset, String> {
+    let from_end = match rest.chars().next() {
+        Some('+') => false,
+        Some('-') => true,
+        _ => return Err(format!("expected '+' or '-' at '{}'", rest)),
+    };
+    *rest = &rest[1..];
+
+    Ok(Offset {
+        from_end,
+        length: parse_length(rest)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 1000,
+        height: 800,
+    };
+
+    fn around(floating: &[Rect]) -> Surroundings<'_> {
+        Surroundings {
+            area: AREA,
+            parent: None,
+            pointer: None,
+            floating,
+        }
+    }
+
+    #[test]
+    fn requested_only_keeps_the_window_inside_the_area() {
+        let window = Rect::new(900, -20, 200, 100);
+        assert_eq!(
+            place(Placement::Requested, window, &around(&[])),
+            Rect::new(800, 0, 200, 100)
+        );
+    }
+
+    #[test]
+    fn parent_centers_on_the_parent_or_the_area() {
+        let window = Rect::new(0, 0, 200, 100);
+        let mut surroundings = around(&[]);
+        assert_eq!(
+            place(Placement::Parent, window, &surroundings),
+            Rect::new(400, 350, 200, 100)
+        );
+
+        surroundings.parent = Some(Rect::new(100, 100, 400, 300));
+        assert_eq!(
+            place(Placement::Parent, window, &surroundings),
+            Rect::new(200, 200, 200, 100)
+        );
+    }
+
+    #[test]
+    fn least_overlap_goes_next_to_the_other_windows() {
+        let window = Rect::new(0, 0, 200, 100);
+        let floating = [Rect::new(0, 0, 300, 300)];
+        assert_eq!(
+            place(Placement::LeastOverlap, window, &around(&floating)),
+            Rect::new(300, 0, 200, 100)
+        );
+    }
+
+    #[test]
+    fn geometry_parses_sizes_and_offsets() {
+        assert_eq!(
+            Geometry::parse("40%x300-0+10"),
+            Ok(Geometry {
+                size: Some((Length::Percent(40), Length::Pixels(300))),
+                position: Some((
+                    Offset {
+                        from_end: true,
+                        length: Length::Pixels(0),
+                    },
+                    Offset {
+                        from_end: false,
+                        length: Length::Pixels(10),
+                    },
+                )),
+            })
+        );
+        assert_eq!(
+            Geometry::parse("-0+0").map(|geometry| geometry.size),
+            Ok(None)
+        );
+
+        for broken in ["", "40%", "0x100", "120%x50%", "100x100+5", "1x1+2+3x"]
+        {
+            assert!(Geometry::parse(broken).is_err(), "{}", broken);
+        }
+    }
+
+    #[test]
+    fn geometry_applies_inside_the_area() {
+        let window = Rect::new(10, 10, 100, 100);
+        let geometry = Geometry::parse("50%x200-0-0").unwrap();
+        assert_eq!(
+            geometry.apply(window, 1, AREA),
+            Rect::new(500, 598, 500, 202)
+        );
+    }
+}
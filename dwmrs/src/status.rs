@@ -0,0 +1,167 @@
+//! Status text markup
+//!
+//! The status text can change how it is drawn with markup between two `^`,
+//! the same markup status2d uses:
+//!
+//! - `^c#rrggbb^` draws the text after it in another color
+//! - `^b#rrggbb^` changes the background
+//! - `^rX,Y,W,H^` draws a rectangle in the text color at X, Y relative to
+//!   the current position, it doesn't move the position
+//! - `^fN^` moves the position N pixels forward
+//! - `^d^` goes back to the colors of the bar
+//!
+//! Anything else between two `^` is not markup and is drawn as it is.
+//!
+//! The bytes 1 to 30 split the status text into blocks the way dwmblocks
+//! writes it, byte `n` starts block `n`. Clicking a block sends signal
+//! `SIGRTMIN + n` to the status program. Tabs, newlines and the other
+//! whitespace in that range stay text, dwmblocks can't signal those blocks.
+
+use crate::layout::Rect;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Markup {
+    Text(String),
+    /// A `#rrggbb` color
+    Foreground(String),
+    Background(String),
+    Rect(Rect),
+    Space(i32),
+    Reset,
+    /// Start of a clickable block
+    Block(u8),
+}
+
+pub fn parse(status: &str) -> Vec<Markup> {
+    let mut items = Vec::new();
+    let mut text_start = 0;
+    let mut search = 0;
+
+    while let Some(open) = status[search..].find('^').map(|i| search + i) {
+        let Some(close) = status[open + 1..].find('^').map(|i| open + 1 + i)
+        else {
+            break;
+        };
+
+        match parse_markup(&status[open + 1..close]) {
+            Some(markup) => {
+                push_text(&mut items, &status[text_start..open]);
+                items.push(markup);
+                text_start = close + 1;
+                search = close + 1;
+            }
+            // The closing `^` can still open the next markup
+            None => search = open + 1,
+        }
+    }
+
+    push_text(&mut items, &status[text_start..]);
+
+    items
+}
+
+/// The highest block dwmblocks writes, the real-time signals on Linux only go
+/// up to `SIGRTMIN + 30`
+const LAST_BLOCK: u8 = 30;
+
+fn is_block(byte: u8) -> bool {
+    (1..=LAST_BLOCK).contains(&byte) && !byte.is_ascii_whitespace()
+}
+
+/// Pushes `text` split at the block delimiters
+fn push_text(items: &mut Vec<Markup>, text: &str) {
+    let mut start = 0;
+    for (index, byte) in text.bytes().enumerate() {
+        if is_block(byte) {
+            if start < index {
+                items.push(Markup::Text(text[start..index].to_string()));
+            }
+            items.push(Markup::Block(byte));
+            start = index + 1;
+        }
+    }
+
+    if start < text.len() {
+        items.push(Markup::Text(text[start..].to_string()));
+    }
+}
+
+fn parse_markup(markup: &str) -> Option<Markup> {
+    let mut chars = markup.chars();
+    let kind = chars.next()?;
+    let value = chars.as_str();
+
+    match kind {
+        'c' if is_color(value) => Some(Markup::Foreground(value.to_string())),
+        'b' if is_color(value) => Some(Markup::Background(value.to_string())),
+        'r' => {
+            let numbers = value
+                .split(',')
+                .map(|number| number.parse::<i32>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            let [x, y, width, height] = numbers[..] else {
+                return None;
+            };
+            (width >= 0 && height >= 0)
+                .then_some(Markup::Rect(Rect::new(x, y, width, height)))
+        }
+        'f' => value.parse().ok().map(Markup::Space),
+        'd' if value.is_empty() => Some(Markup::Reset),
+        _ => None,
+    }
+}
+
+fn is_color(value: &str) -> bool {
+    value.len() == 7 &&
+        value.starts_with('#') &&
+        value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Markup {
+        Markup::Text(text.to_string())
+    }
+
+    #[test]
+    fn parse_splits_text_and_markup() {
+        assert_eq!(
+            parse("^c#ff0000^cpu ^r0,2,10,4^^f12^mem^d^"),
+            [
+                Markup::Foreground("#ff0000".to_string()),
+                text("cpu "),
+                Markup::Rect(Rect::new(0, 2, 10, 4)),
+                Markup::Space(12),
+                text("mem"),
+                Markup::Reset,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_keeps_broken_markup_as_text() {
+        assert_eq!(parse("a^b^c"), [text("a^b^c")]);
+        assert_eq!(parse("^c#ff^x"), [text("^c#ff^x")]);
+        assert_eq!(parse("^r1,2,3^ ^"), [text("^r1,2,3^ ^")]);
+        assert_eq!(
+            parse("2^3^c#000000^"),
+            [text("2^3"), Markup::Foreground("#000000".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_splits_blocks() {
+        assert_eq!(
+            parse("\x01cpu\tx\n\x02^c#ffffff^mem\x1f"),
+            [
+                Markup::Block(1),
+                text("cpu\tx\n"),
+                Markup::Block(2),
+                Markup::Foreground("#ffffff".to_string()),
+                text("mem\x1f"),
+            ]
+        );
+    }
+}